@@ -0,0 +1,263 @@
+//! Derive macros for the `image-canvas` crate.
+//!
+//! These are re-exported by `image-canvas` when its `derive` feature is enabled, see the
+//! documentation there.
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, parse_quote, Data, DeriveInput, Error, Expr, Fields, Ident, Lit};
+
+/// Implement `AsPixel` for a `#[repr(C)]` struct that is `bytemuck::Pod`.
+///
+/// Generic structs require all their type parameters to be `Pod` as well.
+///
+/// The struct attribute `#[texel(kind = UNorm, channels = Rgba)]` additionally describes the
+/// channels, where each field is one channel. A channel has as many bits as its field unless
+/// overridden with the field attribute `#[texel(bits = 10)]`.
+#[proc_macro_derive(AsPixel, attributes(texel))]
+pub fn derive_as_pixel(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2, Error> {
+    let name = &input.ident;
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "`AsPixel` can only be derived for structs",
+            ))
+        }
+    };
+
+    // The size of the struct and the order of its channels are only known with a fixed layout.
+    if !has_repr_c(&input.attrs)? {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "`AsPixel` can only be derived for `#[repr(C)]` structs",
+        ));
+    }
+
+    let mut generics = input.generics.clone();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let where_clause = generics.make_where_clause();
+    for param in input.generics.type_params() {
+        let ident = &param.ident;
+        where_clause
+            .predicates
+            .push(parse_quote!(#ident: ::canvas::__bytemuck::Pod));
+    }
+    where_clause
+        .predicates
+        .push(parse_quote!(#name #ty_generics: ::canvas::__bytemuck::Pod));
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    let texel = match texel_attribute(&input.attrs)? {
+        Some((kind, channels)) => {
+            let bits = channel_bits(fields)?;
+            quote! {
+                fn texel() -> ::core::option::Option<::canvas::layout::Texel> {
+                    ::core::option::Option::Some(::canvas::layout::Texel::new(
+                        ::canvas::layout::SampleKind::#kind,
+                        ::canvas::layout::Channels::#channels,
+                        [#(#bits),*],
+                    ))
+                }
+            }
+        }
+        None => quote!(),
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::canvas::AsPixel for #name #ty_generics #where_clause {
+            fn pixel() -> ::canvas::Pixel<Self> {
+                const { ::canvas::Pixel::<Self>::for_pod() }
+            }
+
+            #texel
+        }
+    })
+}
+
+/// Check for `C` in the `#[repr(..)]` attributes of the struct.
+fn has_repr_c(attrs: &[syn::Attribute]) -> Result<bool, Error> {
+    let mut repr_c = false;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("repr")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("C") {
+                repr_c = true;
+            } else if meta.input.peek(syn::token::Paren) {
+                // Skip the arguments of hints such as `align(4)`.
+                let content;
+                syn::parenthesized!(content in meta.input);
+                content.parse::<TokenStream2>()?;
+            }
+            Ok(())
+        })?;
+    }
+
+    Ok(repr_c)
+}
+
+/// Parse the kind and channels of `#[texel(kind = .., channels = ..)]` on the struct.
+fn texel_attribute(attrs: &[syn::Attribute]) -> Result<Option<(Ident, Ident)>, Error> {
+    let attr = match attrs.iter().find(|attr| attr.path().is_ident("texel")) {
+        Some(attr) => attr,
+        None => return Ok(None),
+    };
+
+    let (mut kind, mut channels) = (None, None);
+    attr.parse_nested_meta(|meta| {
+        let value: Ident = meta.value()?.parse()?;
+        if meta.path.is_ident("kind") {
+            kind = Some(value);
+        } else if meta.path.is_ident("channels") {
+            channels = Some(value);
+        } else {
+            return Err(meta.error("expected `kind` or `channels`"));
+        }
+        Ok(())
+    })?;
+
+    match (kind, channels) {
+        (Some(kind), Some(channels)) => Ok(Some((kind, channels))),
+        _ => Err(Error::new_spanned(
+            attr,
+            "expected both `kind` and `channels` in `texel` attribute",
+        )),
+    }
+}
+
+/// The number of bits of each channel, padded to four channels.
+fn channel_bits(fields: &Fields) -> Result<Vec<TokenStream2>, Error> {
+    if fields.len() > 4 {
+        return Err(Error::new_spanned(
+            fields,
+            "a texel can describe at most four channels",
+        ));
+    }
+
+    let mut bits = Vec::new();
+    for field in fields {
+        let ty = &field.ty;
+        let mut explicit = None;
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("texel"))
+        {
+            // Also rejects the forms `#[texel]` and `#[texel = ..]`.
+            attr.parse_nested_meta(|meta| {
+                if !meta.path.is_ident("bits") {
+                    return Err(meta.error("expected `bits`"));
+                }
+                match meta.value()?.parse()? {
+                    Expr::Lit(lit) => match lit.lit {
+                        Lit::Int(int) => explicit = Some(int.base10_parse::<u8>()?),
+                        other => return Err(Error::new_spanned(other, "expected an integer")),
+                    },
+                    other => return Err(Error::new_spanned(other, "expected an integer")),
+                }
+                Ok(())
+            })?;
+        }
+
+        bits.push(match explicit {
+            Some(explicit) => quote!(#explicit),
+            None => quote!((::core::mem::size_of::<#ty>() * 8) as u8),
+        });
+    }
+
+    bits.resize(4, quote!(0u8));
+    Ok(bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(input: DeriveInput) -> String {
+        expand(input).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn generic_bounds() {
+        let output = expand(parse_quote! {
+            #[repr(C, align(4))]
+            struct Pair<T> where T: Copy {
+                a: T,
+                b: T,
+            }
+        })
+        .unwrap()
+        .to_string();
+
+        let bounds =
+            quote!(where T: Copy, T: ::canvas::__bytemuck::Pod, Pair<T>: ::canvas::__bytemuck::Pod);
+        assert!(output.contains(&bounds.to_string()), "{}", output);
+    }
+
+    #[test]
+    fn rejected_structs() {
+        let missing_repr = error(parse_quote! {
+            struct Gray { v: u8 }
+        });
+        assert!(missing_repr.contains("repr(C)"), "{}", missing_repr);
+
+        let transparent = error(parse_quote! {
+            #[repr(transparent)]
+            struct Gray { v: u8 }
+        });
+        assert!(transparent.contains("repr(C)"), "{}", transparent);
+
+        let enumeration = error(parse_quote! {
+            #[repr(C)]
+            enum Gray { V }
+        });
+        assert!(enumeration.contains("structs"), "{}", enumeration);
+    }
+
+    #[test]
+    fn rejected_attributes() {
+        let path = error(parse_quote! {
+            #[repr(C)]
+            #[texel(kind = UNorm, channels = Gray)]
+            struct Gray { #[texel] v: u8 }
+        });
+        assert!(path.contains("expected"), "{}", path);
+
+        let name_value = error(parse_quote! {
+            #[repr(C)]
+            #[texel(kind = UNorm, channels = Gray)]
+            struct Gray { #[texel = 8] v: u8 }
+        });
+        assert!(name_value.contains("expected"), "{}", name_value);
+
+        let struct_path = error(parse_quote! {
+            #[repr(C)]
+            #[texel]
+            struct Gray { v: u8 }
+        });
+        assert!(struct_path.contains("expected"), "{}", struct_path);
+
+        let incomplete = error(parse_quote! {
+            #[repr(C)]
+            #[texel(kind = UNorm)]
+            struct Gray { v: u8 }
+        });
+        assert!(incomplete.contains("both"), "{}", incomplete);
+
+        let too_many = error(parse_quote! {
+            #[repr(C)]
+            #[texel(kind = UNorm, channels = Rgba)]
+            struct Five { a: u8, b: u8, c: u8, d: u8, e: u8 }
+        });
+        assert!(too_many.contains("four channels"), "{}", too_many);
+    }
+}
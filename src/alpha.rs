@@ -0,0 +1,537 @@
+//! Conversion between straight and premultiplied alpha.
+//!
+//! All integer conversions round exactly, that is the result is the representable value closest
+//! to the mathematically exact result. Note that converting to premultiplied alpha loses precision
+//! for all but opaque pixels so that the conversion back is not an exact inverse.
+//!
+//! Since applying a conversion twice silently corrupts an image the interpretation can be recorded
+//! in the layout with [`AlphaLayout`]. The conversion of a canvas with such a layout is a no-op if
+//! its pixels are already in the requested mode.
+//!
+//! ```
+//! use canvas::{Canvas, Layout};
+//! use canvas::alpha::{self, AlphaLayout, AlphaMode};
+//!
+//! let layout = Layout::<[u8; 4]>::width_and_height(1, 1).unwrap();
+//! let layout = AlphaLayout::new(layout, AlphaMode::Straight);
+//! let mut canvas = Canvas::with_bytes(layout, &[0xff, 0x80, 0x00, 0x80]);
+//!
+//! alpha::convert(&mut canvas, AlphaMode::Premultiplied);
+//! alpha::convert(&mut canvas, AlphaMode::Premultiplied);
+//! assert_eq!(canvas.as_slice(), &[[0x80, 0x40, 0x00, 0x80]]);
+//! ```
+use core::convert::TryInto;
+
+use crate::canvas::Canvas;
+use crate::drm::FourCC;
+use crate::layout::{Decay, Layout, Mend, SampleSlice, Take};
+use crate::stride::{ByteCanvasMut, StrideLayout, Strided};
+use crate::Pixel;
+
+/// The interpretation of the alpha channel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AlphaMode {
+    /// The color channels are independent of the alpha channel.
+    Straight,
+    /// The color channels have already been multiplied with the alpha channel.
+    Premultiplied,
+}
+
+/// A layout tagged with the interpretation of its alpha channel.
+///
+/// This strictly adds information to the inner layout, to which it can always decay. Tag an
+/// existing canvas by mending it with an `AlphaMode`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AlphaLayout<L> {
+    layout: L,
+    mode: AlphaMode,
+}
+
+/// A pixel with an alpha channel that can be converted between alpha modes.
+///
+/// This is implemented for arrays of four channels in RGBA order, where integer channels are
+/// normalized to their maximum value.
+pub trait Premultiply: Copy {
+    /// Multiply the color channels with the alpha channel.
+    fn premultiplied(self) -> Self;
+    /// Divide the color channels by the alpha channel.
+    ///
+    /// Colors of fully transparent pixels are undefined. They are set to zero.
+    fn unpremultiplied(self) -> Self;
+}
+
+/// An error indicating that a format has no supported alpha channel.
+#[derive(Debug, Default, PartialEq, Eq, Hash)]
+pub struct UnsupportedFormatError {
+    _private: (),
+}
+
+/// The description of a packed format with an alpha channel.
+struct Packed {
+    bytes: usize,
+    big_endian: bool,
+    alpha_shift: u32,
+    alpha_bits: u32,
+    color_shifts: [u32; 3],
+    color_bits: u32,
+    /// The channels are half-precision floats instead of normalized integers.
+    float: bool,
+}
+
+impl<L> AlphaLayout<L> {
+    /// Tag a layout with an alpha mode.
+    pub fn new(layout: L, mode: AlphaMode) -> Self {
+        AlphaLayout { layout, mode }
+    }
+
+    /// Get the alpha mode of the pixels.
+    pub fn mode(&self) -> AlphaMode {
+        self.mode
+    }
+
+    /// Get the untagged layout.
+    pub fn layout(&self) -> &L {
+        &self.layout
+    }
+
+    /// Remove the tag.
+    pub fn into_inner(self) -> L {
+        self.layout
+    }
+}
+
+impl Packed {
+    fn from_fourcc(fourcc: FourCC) -> Option<Self> {
+        let big_endian = fourcc.is_big_endian();
+        let new = |bytes, alpha: (u32, u32), colors, bits| Packed {
+            bytes,
+            big_endian,
+            alpha_shift: alpha.0,
+            alpha_bits: alpha.1,
+            color_shifts: colors,
+            color_bits: bits,
+            float: bits == 16,
+        };
+
+        // The order of color channels does not matter, all are treated the same.
+        Some(match fourcc.little_endian() {
+            FourCC::ARGB444 | FourCC::ABGR444 => new(2, (12, 4), [0, 4, 8], 4),
+            FourCC::RGBA444 | FourCC::BGRA444 => new(2, (0, 4), [4, 8, 12], 4),
+            FourCC::ARGB8888 | FourCC::ABGR8888 => new(4, (24, 8), [0, 8, 16], 8),
+            FourCC::RGBA8888 | FourCC::BGRA8888 => new(4, (0, 8), [8, 16, 24], 8),
+            FourCC::ARGB2101010 | FourCC::ABGR2101010 => new(4, (30, 2), [0, 10, 20], 10),
+            FourCC::RGBA1010102 | FourCC::BGRA1010102 => new(4, (0, 2), [2, 12, 22], 10),
+            FourCC::ARGB16161616F | FourCC::ABGR16161616F => new(8, (48, 16), [0, 16, 32], 16),
+            _ => return None,
+        })
+    }
+
+    fn convert(&self, mut canvas: ByteCanvasMut<'_>, mode: AlphaMode) {
+        let spec = canvas.layout().spec();
+        assert_eq!(spec.element.size(), self.bytes, "Mismatching pixel size.");
+
+        let alpha_max = (1u64 << self.alpha_bits) - 1;
+        let color_max = (1u64 << self.color_bits) - 1;
+
+        for y in 0..spec.height {
+            for x in 0..spec.width {
+                let bytes = canvas.element_bytes_mut(x, y);
+                let mut value = match (self.bytes, self.big_endian) {
+                    (2, false) => u64::from(u16::from_le_bytes(bytes.try_into().unwrap())),
+                    (2, true) => u64::from(u16::from_be_bytes(bytes.try_into().unwrap())),
+                    (4, false) => u64::from(u32::from_le_bytes(bytes.try_into().unwrap())),
+                    (4, true) => u64::from(u32::from_be_bytes(bytes.try_into().unwrap())),
+                    (_, false) => u64::from_le_bytes(bytes.try_into().unwrap()),
+                    (_, true) => u64::from_be_bytes(bytes.try_into().unwrap()),
+                };
+
+                let alpha = (value >> self.alpha_shift) & alpha_max;
+                for &shift in &self.color_shifts {
+                    let color = (value >> shift) & color_max;
+                    let color = if self.float {
+                        let (color, alpha) = (half_to_f32(color as u16), half_to_f32(alpha as u16));
+                        let [color, ..] = match mode {
+                            AlphaMode::Premultiplied => [color, 0.0, 0.0, alpha].premultiplied(),
+                            AlphaMode::Straight => [color, 0.0, 0.0, alpha].unpremultiplied(),
+                        };
+                        u64::from(f32_to_half(color))
+                    } else {
+                        match mode {
+                            AlphaMode::Premultiplied => mul_round(color, alpha, alpha_max),
+                            AlphaMode::Straight => div_round(color, alpha, alpha_max, color_max),
+                        }
+                    };
+                    value = value & !(color_max << shift) | color << shift;
+                }
+
+                match (self.bytes, self.big_endian) {
+                    (2, false) => bytes.copy_from_slice(&(value as u16).to_le_bytes()),
+                    (2, true) => bytes.copy_from_slice(&(value as u16).to_be_bytes()),
+                    (4, false) => bytes.copy_from_slice(&(value as u32).to_le_bytes()),
+                    (4, true) => bytes.copy_from_slice(&(value as u32).to_be_bytes()),
+                    (_, false) => bytes.copy_from_slice(&value.to_le_bytes()),
+                    (_, true) => bytes.copy_from_slice(&value.to_be_bytes()),
+                }
+            }
+        }
+    }
+}
+
+/// Compute `color * alpha / max`, rounded to nearest.
+fn mul_round(color: u64, alpha: u64, max: u64) -> u64 {
+    (2 * color * alpha + max) / (2 * max)
+}
+
+/// Compute `color * max / alpha`, rounded to nearest and saturating at `color_max`.
+fn div_round(color: u64, alpha: u64, max: u64, color_max: u64) -> u64 {
+    if alpha == 0 {
+        0
+    } else {
+        ((2 * color * max + alpha) / (2 * alpha)).min(color_max)
+    }
+}
+
+/// Widen a half-precision float, exactly.
+fn half_to_f32(half: u16) -> f32 {
+    let sign = u32::from(half & 0x8000) << 16;
+    let exponent = u32::from(half >> 10 & 0x1f);
+    let mantissa = u32::from(half & 0x3ff);
+
+    let bits = match exponent {
+        0 => {
+            // Zero and subnormals, with a value of `mantissa * 2^-24`.
+            let value = mantissa as f32 / (1u32 << 24) as f32;
+            return if sign == 0 { value } else { -value };
+        }
+        0x1f => sign | 0x7f80_0000 | mantissa << 13,
+        _ => sign | (exponent + 112) << 23 | mantissa << 13,
+    };
+
+    f32::from_bits(bits)
+}
+
+/// Narrow to a half-precision float, rounded to nearest with ties to even.
+fn f32_to_half(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = (bits >> 16 & 0x8000) as u16;
+    let exponent = (bits >> 23 & 0xff) as i32;
+    let mantissa = bits & 0x7f_ffff;
+
+    if exponent == 0xff {
+        let nan = if mantissa != 0 { 0x200 } else { 0 };
+        return sign | 0x7c00 | nan;
+    }
+
+    // The biased exponent of the half-precision float.
+    let exponent = exponent - 112;
+    if exponent >= 0x1f {
+        return sign | 0x7c00;
+    }
+
+    // Shift out the excess mantissa bits. Subnormals also lose the implicit leading bit.
+    let (mantissa, shift) = if exponent > 0 {
+        ((exponent as u32) << 23 | mantissa, 13)
+    } else if exponent >= -10 {
+        (0x80_0000 | mantissa, (14 - exponent) as u32)
+    } else {
+        return sign;
+    };
+
+    let half = mantissa >> shift;
+    let rest = mantissa & ((1 << shift) - 1);
+    let halfway = 1 << (shift - 1);
+    let round = rest > halfway || (rest == halfway && half & 1 == 1);
+    // A carry out of the mantissa correctly increments the exponent, up to infinity.
+    sign | (half + u32::from(round)) as u16
+}
+
+/// Convert pixels with straight alpha to premultiplied alpha.
+pub fn premultiply<P: Premultiply>(pixels: &mut [P]) {
+    pixels.iter_mut().for_each(|p| *p = p.premultiplied());
+}
+
+/// Convert pixels with premultiplied alpha to straight alpha.
+pub fn unpremultiply<P: Premultiply>(pixels: &mut [P]) {
+    pixels.iter_mut().for_each(|p| *p = p.unpremultiplied());
+}
+
+/// Convert the pixels of a tagged canvas to another alpha mode.
+///
+/// This does nothing if the pixels are already in the requested mode. Otherwise the pixels are
+/// converted and the tag is updated.
+pub fn convert<L>(canvas: &mut Canvas<AlphaLayout<L>>, mode: AlphaMode)
+where
+    L: SampleSlice,
+    L::Sample: Premultiply,
+{
+    if canvas.layout().mode == mode {
+        return;
+    }
+
+    match mode {
+        AlphaMode::Premultiplied => premultiply(canvas.as_mut_slice()),
+        AlphaMode::Straight => unpremultiply(canvas.as_mut_slice()),
+    }
+
+    canvas.layout_mut_unguarded().mode = mode;
+}
+
+/// Convert a canvas with a DRM format from straight to premultiplied alpha.
+///
+/// Supported are all packed RGB formats with an alpha channel, in either byte order. Channels of
+/// the half-float formats are converted through `f32`. The format must be described by the element
+/// of the canvas, such as for the planes of the format's layout.
+///
+/// # Panics
+///
+/// This function panics if the element size of the canvas is not that of the format.
+pub fn premultiply_drm(
+    canvas: ByteCanvasMut<'_>,
+    fourcc: FourCC,
+) -> Result<(), UnsupportedFormatError> {
+    let packed = Packed::from_fourcc(fourcc).ok_or_else(UnsupportedFormatError::default)?;
+    packed.convert(canvas, AlphaMode::Premultiplied);
+    Ok(())
+}
+
+/// Convert a canvas with a DRM format from premultiplied to straight alpha.
+///
+/// See [`premultiply_drm`] for the supported formats.
+///
+/// # Panics
+///
+/// This function panics if the element size of the canvas is not that of the format.
+pub fn unpremultiply_drm(
+    canvas: ByteCanvasMut<'_>,
+    fourcc: FourCC,
+) -> Result<(), UnsupportedFormatError> {
+    let packed = Packed::from_fourcc(fourcc).ok_or_else(UnsupportedFormatError::default)?;
+    packed.convert(canvas, AlphaMode::Straight);
+    Ok(())
+}
+
+macro_rules! premultiply_integer {
+    ($($ty:ty),*) => {
+        $(impl Premultiply for [$ty; 4] {
+            fn premultiplied(self) -> Self {
+                let max = u64::from(<$ty>::MAX);
+                let alpha = u64::from(self[3]);
+                let color = |c: $ty| mul_round(u64::from(c), alpha, max) as $ty;
+                [color(self[0]), color(self[1]), color(self[2]), self[3]]
+            }
+
+            fn unpremultiplied(self) -> Self {
+                let max = u64::from(<$ty>::MAX);
+                let alpha = u64::from(self[3]);
+                let color = |c: $ty| div_round(u64::from(c), alpha, max, max) as $ty;
+                [color(self[0]), color(self[1]), color(self[2]), self[3]]
+            }
+        })*
+    };
+}
+
+premultiply_integer!(u8, u16);
+
+impl Premultiply for [f32; 4] {
+    fn premultiplied(self) -> Self {
+        let [r, g, b, a] = self;
+        [r * a, g * a, b * a, a]
+    }
+
+    fn unpremultiplied(self) -> Self {
+        let [r, g, b, a] = self;
+        if a == 0.0 {
+            [0.0, 0.0, 0.0, a]
+        } else {
+            [r / a, g / a, b / a, a]
+        }
+    }
+}
+
+impl<L: Layout> Layout for AlphaLayout<L> {
+    fn byte_len(&self) -> usize {
+        self.layout.byte_len()
+    }
+}
+
+impl<L: Take> Take for AlphaLayout<L> {
+    fn take(&mut self) -> Self {
+        AlphaLayout {
+            layout: self.layout.take(),
+            mode: self.mode,
+        }
+    }
+}
+
+impl<L: SampleSlice> SampleSlice for AlphaLayout<L> {
+    type Sample = L::Sample;
+
+    fn sample(&self) -> Pixel<L::Sample> {
+        self.layout.sample()
+    }
+}
+
+impl<L: Strided> Strided for AlphaLayout<L> {
+    fn strided(&self) -> StrideLayout {
+        self.layout.strided()
+    }
+}
+
+/// Tag a layout with the alpha mode.
+impl<L: Clone> Mend<L> for AlphaMode
+where
+    AlphaLayout<L>: Layout,
+{
+    type Into = AlphaLayout<L>;
+
+    fn mend(self, from: &L) -> AlphaLayout<L> {
+        AlphaLayout::new(from.clone(), self)
+    }
+}
+
+/// Remove the alpha tag from a matrix layout.
+impl<P> Decay<AlphaLayout<crate::Layout<P>>> for crate::Layout<P> {
+    fn decay(from: AlphaLayout<crate::Layout<P>>) -> Self {
+        from.layout
+    }
+}
+
+/// Remove the alpha tag from a strided layout.
+impl Decay<AlphaLayout<StrideLayout>> for StrideLayout {
+    fn decay(from: AlphaLayout<StrideLayout>) -> Self {
+        from.layout
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Matrix;
+
+    #[test]
+    fn exact_rounding() {
+        for alpha in 0..=255u8 {
+            for color in 0..=255u8 {
+                let exact = f64::from(color) * f64::from(alpha) / 255.0;
+                let [result, _, _, _] = [color, 0, 0, alpha].premultiplied();
+                assert!(
+                    (f64::from(result) - exact).abs() <= 0.5,
+                    "{} {}",
+                    color,
+                    alpha
+                );
+            }
+        }
+
+        assert_eq!([0x8000u16, 0, 0, 0x8000].premultiplied()[0], 0x4000);
+        assert_eq!([0x4000u16, 0, 0, 0x8000].unpremultiplied()[0], 0x8000);
+        assert_eq!(
+            [1.0f32, 0.5, 0.0, 0.5].premultiplied(),
+            [0.5, 0.25, 0.0, 0.5]
+        );
+        assert_eq!(
+            [0.5f32, 0.25, 0.0, 0.5].unpremultiplied(),
+            [1.0, 0.5, 0.0, 0.5]
+        );
+    }
+
+    #[test]
+    fn roundtrip_opaque() {
+        let mut matrix = Matrix::<[u8; 4]>::with_width_and_height(16, 16);
+        matrix
+            .as_mut_slice()
+            .iter_mut()
+            .enumerate()
+            .for_each(|(idx, p)| *p = [idx as u8, !(idx as u8), 0x42, 0xff]);
+        let original = matrix.clone();
+
+        premultiply(matrix.as_mut_slice());
+        assert!(matrix == original);
+        unpremultiply(matrix.as_mut_slice());
+        assert!(matrix == original);
+    }
+
+    #[test]
+    fn tagged_canvas() {
+        let layout = crate::Layout::<[u8; 4]>::width_and_height(1, 1).unwrap();
+        let canvas = Canvas::with_bytes(layout, &[0xff, 0xff, 0xff, 0x00]);
+        let mut canvas = canvas.mended(AlphaMode::Premultiplied);
+
+        convert(&mut canvas, AlphaMode::Premultiplied);
+        assert_eq!(canvas.as_slice(), &[[0xff, 0xff, 0xff, 0x00]]);
+        convert(&mut canvas, AlphaMode::Straight);
+        assert_eq!(canvas.layout().mode(), AlphaMode::Straight);
+        assert_eq!(canvas.as_slice(), &[[0x00, 0x00, 0x00, 0x00]]);
+
+        let canvas: Canvas<crate::Layout<[u8; 4]>> = canvas.decay();
+        assert_eq!(canvas.as_slice().len(), 1);
+    }
+
+    #[test]
+    fn drm_formats() {
+        let mut matrix = Matrix::<u32>::with_width_and_height(2, 1);
+        // Alpha of 1/3, color channels at their maximum and 0x30.
+        matrix[(0, 0)] = 1 << 30 | 0x3ff << 20 | 0x30 << 10 | 0x3ff;
+        // Fully transparent.
+        matrix[(1, 0)] = 0x3ff << 20;
+
+        premultiply_drm((&mut matrix).into(), FourCC::ARGB2101010).unwrap();
+        assert_eq!(matrix[(0, 0)], 1 << 30 | 0x155 << 20 | 0x10 << 10 | 0x155);
+        assert_eq!(matrix[(1, 0)], 0);
+
+        unpremultiply_drm((&mut matrix).into(), FourCC::ARGB2101010).unwrap();
+        assert_eq!(matrix[(0, 0)], 1 << 30 | 0x3ff << 20 | 0x30 << 10 | 0x3ff);
+
+        let mut matrix = Matrix::<[u8; 4]>::with_width_and_height(1, 1);
+        // Blue, green, red and alpha in memory.
+        matrix[(0, 0)] = [0xff, 0x80, 0x00, 0x80];
+        premultiply_drm((&mut matrix).into(), FourCC::ARGB8888).unwrap();
+        assert_eq!(matrix[(0, 0)], [0x80, 0x40, 0x00, 0x80]);
+
+        assert!(premultiply_drm((&mut matrix).into(), FourCC::XRGB8888).is_err());
+    }
+
+    #[test]
+    fn half_float_formats() {
+        let pixel = crate::pixel::constants::U16.array4();
+        let layout = crate::Layout::width_and_height_for_pixel(pixel, 1, 1).unwrap();
+        let mut matrix = Matrix::with_layout(layout);
+        // Blue of 0.0, green of 1.0 and red of -2.0 with an alpha of 0.5, in memory.
+        matrix[(0, 0)] = [0x0000, 0x3c00, 0xc000, 0x3800];
+
+        premultiply_drm((&mut matrix).into(), FourCC::ARGB16161616F).unwrap();
+        assert_eq!(matrix[(0, 0)], [0x0000, 0x3800, 0xbc00, 0x3800]);
+        unpremultiply_drm((&mut matrix).into(), FourCC::ABGR16161616F).unwrap();
+        assert_eq!(matrix[(0, 0)], [0x0000, 0x3c00, 0xc000, 0x3800]);
+
+        let mut matrix = Matrix::<[u8; 8]>::with_width_and_height(1, 1);
+        // Alpha of 0.5, red of 1.0, green and blue of 0.0, as a big endian value.
+        matrix[(0, 0)] = [0x38, 0x00, 0x3c, 0x00, 0x00, 0x00, 0x00, 0x00];
+        premultiply_drm((&mut matrix).into(), FourCC::ARGB16161616F.big_endian()).unwrap();
+        assert_eq!(
+            matrix[(0, 0)],
+            [0x38, 0x00, 0x38, 0x00, 0x00, 0x00, 0x00, 0x00]
+        );
+    }
+
+    #[test]
+    fn half_conversion() {
+        for half in 0..=0xffffu16 {
+            let value = half_to_f32(half);
+            if value.is_nan() {
+                assert!(f32_to_half(value) & 0x7fff > 0x7c00, "{:x}", half);
+            } else {
+                assert_eq!(f32_to_half(value), half, "{:x}", half);
+            }
+        }
+
+        assert_eq!(half_to_f32(0x0001), 1.0 / (1u32 << 24) as f32);
+        assert_eq!(half_to_f32(0x7bff), 65504.0);
+        // Ties round to even, in normal and subnormal range.
+        assert_eq!(f32_to_half(1.0 + 1.0 / 2048.0), 0x3c00);
+        assert_eq!(f32_to_half(1.0 + 3.0 / 2048.0), 0x3c02);
+        assert_eq!(f32_to_half(1.5 / (1u32 << 24) as f32), 0x0002);
+        assert_eq!(f32_to_half(65520.0), 0x7c00);
+        assert_eq!(f32_to_half(1e-10), 0x0000);
+    }
+}
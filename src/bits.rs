@@ -0,0 +1,390 @@
+//! Images with less than one byte per pixel.
+//!
+//! Monochrome bitmaps, masks and palette images with few colors pack several pixels into each
+//! byte. These can not be described by an [`Element`](crate::layout::Element) which always covers
+//! whole bytes. Instead, a [`BitLayout`] describes rows of 1, 2 or 4 bit pixels where each row
+//! starts at a new byte and may be followed by additional padding. The first row may start at an
+//! offset into the buffer, such as a plane of a DRM frame buffer.
+//!
+//! ```
+//! use canvas::Matrix;
+//! use canvas::bits::{BitLayout, BitMatrix, BitOrder};
+//! use canvas::layout::Coord;
+//!
+//! let layout = BitLayout::new(1, BitOrder::MsbFirst, 10, 2).unwrap();
+//! let mut bitmap = BitMatrix::new(layout);
+//! bitmap.set(Coord(0, 0), 1);
+//! bitmap.set(Coord(9, 1), 1);
+//! assert_eq!(bitmap.as_bytes(), &[0x80, 0x00, 0x00, 0x40]);
+//!
+//! let expanded: Matrix<u8> = bitmap.expand();
+//! assert_eq!(expanded[(9, 1)], 1);
+//! ```
+use core::convert::TryFrom;
+
+use crate::canvas::Canvas;
+use crate::layout::{Coord, Layout};
+use crate::Matrix;
+
+/// The order of pixels within each byte.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BitOrder {
+    /// The first pixel occupies the most significant bits.
+    ///
+    /// This is the order of most file formats and of the DRM formats.
+    MsbFirst,
+    /// The first pixel occupies the least significant bits.
+    LsbFirst,
+}
+
+/// A row-major matrix of pixels with 1, 2 or 4 bits each.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BitLayout {
+    bits: u8,
+    order: BitOrder,
+    width: u32,
+    height: u32,
+    row_stride: usize,
+    offset: usize,
+}
+
+/// An owned image of sub-byte pixels.
+#[derive(Clone, PartialEq, Eq)]
+pub struct BitMatrix {
+    inner: Canvas<BitLayout>,
+}
+
+impl BitLayout {
+    /// Create a layout where each row is padded to the next full byte.
+    ///
+    /// Returns `None` if `bits` is not one of 1, 2 or 4 or if the layout would not fit into the
+    /// address space.
+    pub fn new(bits: u8, order: BitOrder, width: u32, height: u32) -> Option<Self> {
+        if !matches!(bits, 1 | 2 | 4) {
+            return None;
+        }
+
+        let row_bits = u64::from(width) * u64::from(bits);
+        let row_stride = usize::try_from(row_bits.div_ceil(8)).ok()?;
+
+        BitLayout {
+            bits,
+            order,
+            width,
+            height,
+            row_stride: 0,
+            offset: 0,
+        }
+        .with_row_stride(row_stride)
+    }
+
+    /// Change the distance between the starts of two rows, in bytes.
+    ///
+    /// Returns `None` if the stride is smaller than a row or if the layout would not fit into the
+    /// address space.
+    pub fn with_row_stride(self, row_stride: usize) -> Option<Self> {
+        let row_bits = u64::from(self.width) * u64::from(self.bits);
+        if (row_stride as u64) < row_bits.div_ceil(8) {
+            return None;
+        }
+
+        BitLayout { row_stride, ..self }.validate()
+    }
+
+    /// Change the byte offset of the first row within the buffer.
+    ///
+    /// Returns `None` if the layout would not fit into the address space.
+    pub fn with_offset(self, offset: usize) -> Option<Self> {
+        BitLayout { offset, ..self }.validate()
+    }
+
+    fn validate(self) -> Option<Self> {
+        let height = usize::try_from(self.height).ok()?;
+        let len = self.row_stride.checked_mul(height)?;
+        if len.checked_add(self.offset)? > isize::MAX as usize {
+            return None;
+        }
+
+        Some(self)
+    }
+
+    /// The number of bits of each pixel.
+    pub const fn bits(&self) -> u8 {
+        self.bits
+    }
+
+    /// The order of pixels within each byte.
+    pub const fn order(&self) -> BitOrder {
+        self.order
+    }
+
+    /// The width in pixels.
+    pub const fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The height in pixels.
+    pub const fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The distance between the starts of two rows, in bytes.
+    pub const fn row_stride(&self) -> usize {
+        self.row_stride
+    }
+
+    /// The byte offset of the first row.
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The largest value of a pixel.
+    pub const fn max_value(&self) -> u8 {
+        (1 << self.bits) - 1
+    }
+
+    /// The byte index and the shift of a pixel within it.
+    fn position(&self, Coord(x, y): Coord) -> Option<(usize, u32)> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        let bit = x as usize * self.bits as usize;
+        let shift = self.shift((bit % 8) as u32);
+        Some((self.row_start(y as usize) + bit / 8, shift))
+    }
+
+    /// The shift of a pixel starting at some bit within its byte.
+    fn shift(&self, within: u32) -> u32 {
+        match self.order {
+            BitOrder::LsbFirst => within,
+            BitOrder::MsbFirst => 8 - u32::from(self.bits) - within,
+        }
+    }
+
+    /// The byte index of the start of a row.
+    fn row_start(&self, y: usize) -> usize {
+        self.offset + y * self.row_stride
+    }
+
+    /// The number of pixels in each byte.
+    fn pixels_per_byte(&self) -> usize {
+        usize::from(8 / self.bits)
+    }
+}
+
+impl BitMatrix {
+    /// Allocate an image with all pixels set to zero.
+    ///
+    /// # Panics
+    ///
+    /// This method panics when the allocation of the buffer fails.
+    pub fn new(layout: BitLayout) -> Self {
+        BitMatrix {
+            inner: Canvas::new(layout),
+        }
+    }
+
+    /// Create an image by copying packed bytes.
+    ///
+    /// Returns `None` if there are fewer bytes than required by the layout.
+    pub fn with_bytes(layout: BitLayout, bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < layout.byte_len() {
+            return None;
+        }
+
+        Some(BitMatrix {
+            inner: Canvas::with_bytes(layout, &bytes[..layout.byte_len()]),
+        })
+    }
+
+    /// Get the layout of the image.
+    pub fn layout(&self) -> &BitLayout {
+        self.inner.layout()
+    }
+
+    /// Get the packed bytes, including any row padding.
+    pub fn as_bytes(&self) -> &[u8] {
+        self.inner.as_bytes()
+    }
+
+    /// Get the packed bytes mutably, including any row padding.
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        self.inner.as_bytes_mut()
+    }
+
+    /// Get the value of a pixel, if it is within the image.
+    pub fn get(&self, coord: Coord) -> Option<u8> {
+        let layout = *self.layout();
+        let (idx, shift) = layout.position(coord)?;
+        Some(self.as_bytes()[idx] >> shift & layout.max_value())
+    }
+
+    /// Set the value of a pixel.
+    ///
+    /// Only the low bits of the value that fit into a pixel are used.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the coordinate is outside the image.
+    pub fn set(&mut self, coord: Coord, value: u8) {
+        let layout = *self.layout();
+        let (idx, shift) = layout.position(coord).expect("Coordinate out of bounds.");
+        let mask = layout.max_value() << shift;
+        let byte = &mut self.as_bytes_mut()[idx];
+        *byte = (*byte & !mask) | (value << shift & mask);
+    }
+
+    /// Unpack all pixels into a matrix of one byte per pixel.
+    ///
+    /// # Panics
+    ///
+    /// This method panics when the allocation of the matrix fails.
+    pub fn expand(&self) -> Matrix<u8> {
+        let layout = *self.layout();
+        let (width, height) = (layout.width as usize, layout.height as usize);
+        let mut matrix = Matrix::with_width_and_height(width, height);
+        let (bytes, pixels) = (self.as_bytes(), matrix.as_mut_slice());
+
+        for y in 0..height {
+            let row = &bytes[layout.row_start(y)..][..layout.row_stride];
+            let target = &mut pixels[y * width..][..width];
+
+            for (chunk, &byte) in target.chunks_mut(layout.pixels_per_byte()).zip(row) {
+                for (within, pixel) in (0..).step_by(layout.bits.into()).zip(chunk) {
+                    *pixel = byte >> layout.shift(within) & layout.max_value();
+                }
+            }
+        }
+
+        matrix
+    }
+
+    /// Pack all pixels of a matrix of one byte per pixel.
+    ///
+    /// Only the low bits of each value that fit into a pixel are used.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the dimensions of the matrix and the image differ.
+    pub fn pack(&mut self, matrix: &Matrix<u8>) {
+        let layout = *self.layout();
+        assert!(
+            (matrix.layout().width(), matrix.layout().height())
+                == (layout.width as usize, layout.height as usize),
+            "Mismatching dimensions."
+        );
+
+        let width = layout.width as usize;
+        let (pixels, bytes) = (matrix.as_slice(), self.as_bytes_mut());
+
+        for y in 0..layout.height as usize {
+            let row = &mut bytes[layout.row_start(y)..][..layout.row_stride];
+            let source = &pixels[y * width..][..width];
+
+            for (byte, chunk) in row.iter_mut().zip(source.chunks(layout.pixels_per_byte())) {
+                let (mut mask, mut packed) = (0, 0);
+                for (within, &value) in (0..).step_by(layout.bits.into()).zip(chunk) {
+                    let shift = layout.shift(within);
+                    mask |= layout.max_value() << shift;
+                    packed |= (value & layout.max_value()) << shift;
+                }
+
+                // Keep the bits of any padding after the last pixel of the row.
+                *byte = (*byte & !mask) | packed;
+            }
+        }
+    }
+
+    /// Convert into a canvas with the same layout.
+    pub fn into_canvas(self) -> Canvas<BitLayout> {
+        self.inner
+    }
+}
+
+impl Layout for BitLayout {
+    fn byte_len(&self) -> usize {
+        // Does not overflow due to construction.
+        self.offset + self.row_stride * self.height as usize
+    }
+}
+
+impl From<Canvas<BitLayout>> for BitMatrix {
+    fn from(inner: Canvas<BitLayout>) -> Self {
+        BitMatrix { inner }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bit_orders() {
+        let layout = BitLayout::new(2, BitOrder::LsbFirst, 3, 2).unwrap();
+        assert_eq!(layout.row_stride(), 1);
+        assert_eq!(layout.byte_len(), 2);
+
+        let mut image = BitMatrix::new(layout);
+        image.set(Coord(0, 0), 1);
+        image.set(Coord(2, 0), 3);
+        image.set(Coord(1, 1), 0xfe);
+        assert_eq!(image.as_bytes(), &[0b11_00_01, 0b10_00]);
+
+        let layout = BitLayout::new(4, BitOrder::MsbFirst, 3, 2).unwrap();
+        let mut image = BitMatrix::new(layout.with_row_stride(4).unwrap());
+        image.set(Coord(0, 0), 0xa);
+        image.set(Coord(2, 1), 0x5);
+        assert_eq!(image.as_bytes(), &[0xa0, 0, 0, 0, 0, 0x50, 0, 0]);
+        assert_eq!(image.get(Coord(2, 1)), Some(5));
+        assert_eq!(image.get(Coord(3, 1)), None);
+
+        let mut image = BitMatrix::new(layout.with_offset(3).unwrap());
+        image.set(Coord(1, 1), 0xc);
+        assert_eq!(image.as_bytes(), &[0, 0, 0, 0, 0, 0x0c, 0]);
+        assert_eq!(image.expand()[(1, 1)], 0xc);
+
+        assert!(BitLayout::new(3, BitOrder::MsbFirst, 1, 1).is_none());
+        assert!(layout.with_row_stride(1).is_none());
+        assert!(layout.with_offset(isize::MAX as usize).is_none());
+    }
+
+    #[test]
+    fn expand_and_pack() {
+        let mut matrix = Matrix::<u8>::with_width_and_height(9, 3);
+        matrix
+            .as_mut_slice()
+            .iter_mut()
+            .enumerate()
+            .for_each(|(idx, p)| *p = (idx % 3) as u8);
+
+        for &order in &[BitOrder::MsbFirst, BitOrder::LsbFirst] {
+            let layout = BitLayout::new(2, order, 9, 3).unwrap();
+            let mut image = BitMatrix::new(layout);
+            image.pack(&matrix);
+            assert_eq!(image.expand(), matrix, "{:?}", order);
+        }
+
+        for &bits in &[1, 2, 4] {
+            for &order in &[BitOrder::MsbFirst, BitOrder::LsbFirst] {
+                let layout = BitLayout::new(bits, order, 9, 3).unwrap();
+                let layout = layout.with_row_stride(6).unwrap().with_offset(1).unwrap();
+                let mut image = BitMatrix::with_bytes(layout, &[0xff; 19]).unwrap();
+                image.pack(&matrix);
+
+                for (x, y) in (0..9).flat_map(|x| (0..3).map(move |y| (x, y))) {
+                    let value = matrix[(x, y)] & layout.max_value();
+                    assert_eq!(image.get(Coord(x as u32, y as u32)), Some(value));
+                }
+
+                // Padding bits and bytes are untouched.
+                assert_eq!(image.as_bytes()[0], 0xff);
+                assert_eq!(image.as_bytes()[6], 0xff);
+                assert_eq!(
+                    image.expand(),
+                    matrix.clone().map(|p| p & layout.max_value())
+                );
+            }
+        }
+    }
+}
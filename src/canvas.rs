@@ -72,12 +72,15 @@ pub struct ViewMut<'buf, Layout = Bytes> {
 
 /// A raster layout.
 pub trait Raster<Pixel>: Sized {
+    /// The width and height of the raster, in pixels.
     fn dimensions(&self) -> Coord;
+    /// Read the pixel at a coordinate within the dimensions.
     fn get(from: View<Self>, at: Coord) -> Pixel;
 }
 
 /// A raster layout where one can change pixel values independently.
 pub trait RasterMut<Pixel>: Raster<Pixel> {
+    /// Write the pixel at a coordinate within the dimensions.
     fn put(into: ViewMut<Self>, at: Coord, val: Pixel);
 }
 
@@ -262,6 +265,70 @@ impl<L> Canvas<L> {
     {
         self.inner.borrow_mut().into()
     }
+
+    /// Read a pixel of a raster layout.
+    ///
+    /// Returns `None` if the coordinate is outside the dimensions of the layout.
+    pub fn get<P>(&self, at: Coord) -> Option<P>
+    where
+        L: Raster<P> + Clone,
+    {
+        if !Self::contains(self.layout().dimensions(), at) {
+            return None;
+        }
+
+        Some(L::get(self.as_ref(), at))
+    }
+
+    /// Write a pixel of a raster layout.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the coordinate is outside the dimensions of the layout.
+    pub fn put<P>(&mut self, at: Coord, val: P)
+    where
+        L: RasterMut<P> + Clone,
+    {
+        assert!(
+            Self::contains(self.layout().dimensions(), at),
+            "Coordinate out of bounds."
+        );
+        L::put(self.as_mut(), at, val)
+    }
+
+    fn contains(Coord(width, height): Coord, Coord(x, y): Coord) -> bool {
+        x < width && y < height
+    }
+}
+
+/// Methods for read-only views of a canvas.
+impl<'buf, L> View<'buf, L> {
+    /// Get a reference to the layout.
+    pub fn layout(&self) -> &L {
+        &self.inner.layout
+    }
+
+    /// View the buffer as a slice of pixels.
+    ///
+    /// This reinterprets the bytes of the buffer, see [`Canvas::as_pixels`].
+    pub fn as_pixels<P>(&self, pixel: Pixel<P>) -> &'buf [P] {
+        self.inner.buffer.as_pixels(pixel)
+    }
+}
+
+/// Methods for writeable views of a canvas.
+impl<L> ViewMut<'_, L> {
+    /// Get a reference to the layout.
+    pub fn layout(&self) -> &L {
+        &self.inner.layout
+    }
+
+    /// View the buffer as a mutable slice of pixels.
+    ///
+    /// This reinterprets the bytes of the buffer, see [`Canvas::as_mut_pixels`].
+    pub fn as_mut_pixels<P>(&mut self, pixel: Pixel<P>) -> &mut [P] {
+        self.inner.buffer.as_mut_pixels(pixel)
+    }
 }
 
 /// Canvas methods for layouts based on pod samples.
@@ -615,14 +682,17 @@ impl<B: BufferLike, L: SampleSlice> RawCanvas<B, L> {
     }
 
     pub(crate) fn as_slice(&self) -> &[L::Sample] {
-        self.buffer.as_pixels(self.layout.sample())
+        let len = self.layout.byte_len();
+        self.buffer[..len].as_pixels(self.layout.sample())
     }
 
     pub(crate) fn as_mut_slice(&mut self) -> &mut [L::Sample]
     where
         B: BufferMut,
     {
-        self.buffer.as_mut_pixels(self.layout.sample())
+        let len = self.layout.byte_len();
+        let sample = self.layout.sample();
+        self.buffer[..len].as_mut_pixels(sample)
     }
 
     /// Convert back into an vector-like of sample types.
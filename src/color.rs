@@ -0,0 +1,511 @@
+//! Colorimetry metadata that can accompany a layout.
+//!
+//! Layouts deliberately describe bytes and not colors. This module adds a separate descriptor of
+//! the color interpretation, with enumerations following the code points of ITU-T H.273 (also
+//! known as CICP) where applicable. A [`ColorLayout`] pairs it with any layout so that the
+//! information travels along with the pixel data, and can be stripped by decaying the canvas.
+//!
+//! ```
+//! use canvas::{Canvas, Layout};
+//! use canvas::color::{ColorDescriptor, ColorLayout, TransferCharacteristics};
+//!
+//! let layout = Layout::<[u8; 4]>::width_and_height(4, 4).unwrap();
+//! let canvas = Canvas::new(layout).mended(ColorDescriptor::SRGB);
+//! assert_eq!(canvas.layout().color().transfer, TransferCharacteristics::Srgb);
+//!
+//! let canvas: Canvas<Layout<[u8; 4]>> = canvas.decay();
+//! ```
+use crate::alpha::AlphaLayout;
+use crate::drm::{DrmLayout, PlaneLayout};
+use crate::layout::{Decay, DynLayout, Layout, Matrix, Mend, SampleSlice, TMatrix, Take};
+use crate::stride::{StrideLayout, Strided};
+use crate::Pixel;
+
+/// The chromaticity of the primaries and the white point.
+///
+/// The discriminants are the code points of `ColourPrimaries` in H.273.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ColorPrimaries {
+    /// ITU-R BT.709, also used by sRGB.
+    Bt709 = 1,
+    /// Unknown or determined by the application.
+    Unspecified = 2,
+    /// ITU-R BT.470 System M.
+    Bt470M = 4,
+    /// ITU-R BT.470 System B and G, also used by BT.601 625-line systems.
+    Bt470BG = 5,
+    /// SMPTE 170M, also used by BT.601 525-line systems.
+    Smpte170M = 6,
+    /// SMPTE 240M.
+    Smpte240M = 7,
+    /// Generic film with illuminant C.
+    GenericFilm = 8,
+    /// ITU-R BT.2020 and BT.2100.
+    Bt2020 = 9,
+    /// SMPTE ST 428-1, the CIE 1931 XYZ color space.
+    Smpte428 = 10,
+    /// SMPTE RP 431-2, DCI-P3.
+    Smpte431 = 11,
+    /// SMPTE EG 432-1, Display P3.
+    Smpte432 = 12,
+    /// EBU Tech. 3213-E.
+    Ebu3213 = 22,
+}
+
+/// The opto-electronic transfer function of the samples.
+///
+/// The discriminants are the code points of `TransferCharacteristics` in H.273.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TransferCharacteristics {
+    /// ITU-R BT.709.
+    Bt709 = 1,
+    /// Unknown or determined by the application.
+    Unspecified = 2,
+    /// An assumed display gamma of 2.2, ITU-R BT.470 System M.
+    Gamma22 = 4,
+    /// An assumed display gamma of 2.8, ITU-R BT.470 System B and G.
+    Gamma28 = 5,
+    /// SMPTE 170M, also used by BT.601.
+    Smpte170M = 6,
+    /// SMPTE 240M.
+    Smpte240M = 7,
+    /// Linear samples.
+    Linear = 8,
+    /// Logarithmic with a range of 100:1.
+    Log100 = 9,
+    /// Logarithmic with a range of 100·√10:1.
+    Log100Sqrt10 = 10,
+    /// IEC 61966-2-4, xvYCC.
+    Iec61966_2_4 = 11,
+    /// ITU-R BT.1361 extended color gamut.
+    Bt1361 = 12,
+    /// IEC 61966-2-1, sRGB.
+    Srgb = 13,
+    /// ITU-R BT.2020 for 10-bit systems.
+    Bt2020_10 = 14,
+    /// ITU-R BT.2020 for 12-bit systems.
+    Bt2020_12 = 15,
+    /// SMPTE ST 2084 and BT.2100, the perceptual quantizer.
+    Pq = 16,
+    /// SMPTE ST 428-1.
+    Smpte428 = 17,
+    /// ARIB STD-B67 and BT.2100, hybrid log-gamma.
+    Hlg = 18,
+}
+
+/// The coefficients to derive luma and chroma from the primaries.
+///
+/// The discriminants are the code points of `MatrixCoefficients` in H.273.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MatrixCoefficients {
+    /// No matrix, the samples are the primaries themselves such as in RGB.
+    Identity = 0,
+    /// ITU-R BT.709.
+    Bt709 = 1,
+    /// Unknown or determined by the application.
+    Unspecified = 2,
+    /// United States Federal Communications Commission.
+    Fcc = 4,
+    /// ITU-R BT.470 System B and G, also used by BT.601 625-line systems.
+    Bt470BG = 5,
+    /// SMPTE 170M, also used by BT.601 525-line systems.
+    Smpte170M = 6,
+    /// SMPTE 240M.
+    Smpte240M = 7,
+    /// YCgCo.
+    YCgCo = 8,
+    /// ITU-R BT.2020 and BT.2100, non-constant luminance.
+    Bt2020Ncl = 9,
+    /// ITU-R BT.2020, constant luminance.
+    Bt2020Cl = 10,
+    /// SMPTE ST 2085, Y'D'zD'x.
+    Smpte2085 = 11,
+    /// Derived from the chromaticity, non-constant luminance.
+    ChromaticityNcl = 12,
+    /// Derived from the chromaticity, constant luminance.
+    ChromaticityCl = 13,
+    /// ITU-R BT.2100, ICtCp.
+    ICtCp = 14,
+}
+
+/// The range of sample values that is used for the nominal signal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Range {
+    /// Also called video or studio range, such as 16 to 235 for 8-bit luma.
+    Limited,
+    /// All values are used, such as 0 to 255 for 8-bit samples.
+    Full,
+}
+
+/// The position of chroma samples relative to the luma samples when subsampling.
+///
+/// The discriminants are the values of `chroma_sample_loc_type` of H.264 and H.265.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ChromaSiting {
+    /// Vertically centered and horizontally co-sited with the left luma sample.
+    Left = 0,
+    /// Centered between the luma samples.
+    Center = 1,
+    /// Co-sited with the top-left luma sample.
+    TopLeft = 2,
+    /// Horizontally centered and co-sited with the top luma sample.
+    Top = 3,
+    /// Co-sited with the bottom-left luma sample.
+    BottomLeft = 4,
+    /// Horizontally centered and co-sited with the bottom luma sample.
+    Bottom = 5,
+}
+
+/// A complete description of the color interpretation of samples.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ColorDescriptor {
+    /// The primaries and white point.
+    pub primaries: ColorPrimaries,
+    /// The transfer function.
+    pub transfer: TransferCharacteristics,
+    /// The coefficients for luma and chroma, `Identity` for RGB samples.
+    pub matrix: MatrixCoefficients,
+    /// The range of sample values.
+    pub range: Range,
+    /// The position of subsampled chroma, if any.
+    pub chroma_siting: Option<ChromaSiting>,
+}
+
+/// A layout tagged with the color interpretation of its samples.
+///
+/// This strictly adds information to the inner layout, to which it can always decay. Tag an
+/// existing canvas by mending it with a `ColorDescriptor`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ColorLayout<L> {
+    layout: L,
+    color: ColorDescriptor,
+}
+
+impl ColorPrimaries {
+    /// Get the primaries of a code point.
+    pub fn from_code(code: u8) -> Option<Self> {
+        use ColorPrimaries::*;
+        [
+            Bt709,
+            Unspecified,
+            Bt470M,
+            Bt470BG,
+            Smpte170M,
+            Smpte240M,
+            GenericFilm,
+            Bt2020,
+            Smpte428,
+            Smpte431,
+            Smpte432,
+            Ebu3213,
+        ]
+        .iter()
+        .copied()
+        .find(|&p| p as u8 == code)
+    }
+
+    /// Get the code point.
+    pub fn code(self) -> u8 {
+        self as u8
+    }
+}
+
+impl TransferCharacteristics {
+    /// Get the transfer characteristics of a code point.
+    pub fn from_code(code: u8) -> Option<Self> {
+        use TransferCharacteristics::*;
+        [
+            Bt709,
+            Unspecified,
+            Gamma22,
+            Gamma28,
+            Smpte170M,
+            Smpte240M,
+            Linear,
+            Log100,
+            Log100Sqrt10,
+            Iec61966_2_4,
+            Bt1361,
+            Srgb,
+            Bt2020_10,
+            Bt2020_12,
+            Pq,
+            Smpte428,
+            Hlg,
+        ]
+        .iter()
+        .copied()
+        .find(|&t| t as u8 == code)
+    }
+
+    /// Get the code point.
+    pub fn code(self) -> u8 {
+        self as u8
+    }
+}
+
+impl MatrixCoefficients {
+    /// Get the matrix coefficients of a code point.
+    pub fn from_code(code: u8) -> Option<Self> {
+        use MatrixCoefficients::*;
+        [
+            Identity,
+            Bt709,
+            Unspecified,
+            Fcc,
+            Bt470BG,
+            Smpte170M,
+            Smpte240M,
+            YCgCo,
+            Bt2020Ncl,
+            Bt2020Cl,
+            Smpte2085,
+            ChromaticityNcl,
+            ChromaticityCl,
+            ICtCp,
+        ]
+        .iter()
+        .copied()
+        .find(|&m| m as u8 == code)
+    }
+
+    /// Get the code point.
+    pub fn code(self) -> u8 {
+        self as u8
+    }
+}
+
+impl Range {
+    /// Get the range from the `VideoFullRangeFlag` of H.273.
+    pub fn from_full_range_flag(flag: bool) -> Self {
+        if flag {
+            Range::Full
+        } else {
+            Range::Limited
+        }
+    }
+
+    /// Get the `VideoFullRangeFlag` of H.273.
+    pub fn full_range_flag(self) -> bool {
+        self == Range::Full
+    }
+}
+
+impl ChromaSiting {
+    /// Get the siting from a `chroma_sample_loc_type`.
+    pub fn from_code(code: u8) -> Option<Self> {
+        use ChromaSiting::*;
+        [Left, Center, TopLeft, Top, BottomLeft, Bottom]
+            .iter()
+            .copied()
+            .find(|&c| c as u8 == code)
+    }
+
+    /// Get the `chroma_sample_loc_type`.
+    pub fn code(self) -> u8 {
+        self as u8
+    }
+}
+
+impl ColorDescriptor {
+    /// The sRGB color space of IEC 61966-2-1.
+    pub const SRGB: Self = ColorDescriptor {
+        primaries: ColorPrimaries::Bt709,
+        transfer: TransferCharacteristics::Srgb,
+        matrix: MatrixCoefficients::Identity,
+        range: Range::Full,
+        chroma_siting: None,
+    };
+
+    /// Video according to ITU-R BT.709, with chroma sited as in MPEG-2 and later.
+    pub const BT709: Self = ColorDescriptor {
+        primaries: ColorPrimaries::Bt709,
+        transfer: TransferCharacteristics::Bt709,
+        matrix: MatrixCoefficients::Bt709,
+        range: Range::Limited,
+        chroma_siting: Some(ChromaSiting::Left),
+    };
+
+    /// High dynamic range video according to ITU-R BT.2100 with the perceptual quantizer.
+    pub const BT2100_PQ: Self = ColorDescriptor {
+        primaries: ColorPrimaries::Bt2020,
+        transfer: TransferCharacteristics::Pq,
+        matrix: MatrixCoefficients::Bt2020Ncl,
+        range: Range::Limited,
+        chroma_siting: Some(ChromaSiting::TopLeft),
+    };
+
+    /// High dynamic range video according to ITU-R BT.2100 with hybrid log-gamma.
+    pub const BT2100_HLG: Self = ColorDescriptor {
+        transfer: TransferCharacteristics::Hlg,
+        ..Self::BT2100_PQ
+    };
+
+    /// Nothing is known about the color interpretation.
+    pub const UNSPECIFIED: Self = ColorDescriptor {
+        primaries: ColorPrimaries::Unspecified,
+        transfer: TransferCharacteristics::Unspecified,
+        matrix: MatrixCoefficients::Unspecified,
+        range: Range::Full,
+        chroma_siting: None,
+    };
+}
+
+impl<L> ColorLayout<L> {
+    /// Tag a layout with a color descriptor.
+    pub fn new(layout: L, color: ColorDescriptor) -> Self {
+        ColorLayout { layout, color }
+    }
+
+    /// Get the color descriptor of the samples.
+    pub fn color(&self) -> ColorDescriptor {
+        self.color
+    }
+
+    /// Replace the color descriptor.
+    ///
+    /// This does not modify any samples, it only changes their interpretation.
+    pub fn set_color(&mut self, color: ColorDescriptor) {
+        self.color = color;
+    }
+
+    /// Get the untagged layout.
+    pub fn layout(&self) -> &L {
+        &self.layout
+    }
+
+    /// Remove the tag.
+    pub fn into_inner(self) -> L {
+        self.layout
+    }
+}
+
+impl Default for ColorDescriptor {
+    fn default() -> Self {
+        ColorDescriptor::UNSPECIFIED
+    }
+}
+
+impl<L: Layout> Layout for ColorLayout<L> {
+    fn byte_len(&self) -> usize {
+        self.layout.byte_len()
+    }
+}
+
+impl<L: Take> Take for ColorLayout<L> {
+    fn take(&mut self) -> Self {
+        ColorLayout {
+            layout: self.layout.take(),
+            color: self.color,
+        }
+    }
+}
+
+impl<L: SampleSlice> SampleSlice for ColorLayout<L> {
+    type Sample = L::Sample;
+
+    fn sample(&self) -> Pixel<L::Sample> {
+        self.layout.sample()
+    }
+}
+
+impl<L: Strided> Strided for ColorLayout<L> {
+    fn strided(&self) -> StrideLayout {
+        self.layout.strided()
+    }
+}
+
+/// Tag a layout with the color descriptor.
+impl<L: Clone> Mend<L> for ColorDescriptor
+where
+    ColorLayout<L>: Layout,
+{
+    type Into = ColorLayout<L>;
+
+    fn mend(self, from: &L) -> ColorLayout<L> {
+        ColorLayout::new(from.clone(), self)
+    }
+}
+
+// A blanket impl would overlap with the decay of any layout to `Bytes`.
+macro_rules! decay_to_bare {
+    ($(<$($param:ident),*> $layout:ty),* $(,)?) => {
+        $(
+            /// Remove the color tag.
+            impl<$($param),*> Decay<ColorLayout<$layout>> for $layout {
+                fn decay(from: ColorLayout<$layout>) -> Self {
+                    from.layout
+                }
+            }
+        )*
+    };
+}
+
+decay_to_bare!(
+    <P> crate::Layout<P>,
+    <P> TMatrix<P>,
+    <> Matrix,
+    <> DynLayout,
+    <> StrideLayout,
+    <> DrmLayout,
+    <> PlaneLayout,
+);
+
+/// Remove the color tag, keeping the alpha tag.
+impl<L: Layout> Decay<ColorLayout<AlphaLayout<L>>> for AlphaLayout<L> {
+    fn decay(from: ColorLayout<AlphaLayout<L>>) -> Self {
+        from.layout
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alpha::AlphaMode;
+    use crate::Canvas;
+
+    #[test]
+    fn code_points() {
+        for code in 0..=255 {
+            if let Some(primaries) = ColorPrimaries::from_code(code) {
+                assert_eq!(primaries.code(), code);
+            }
+            if let Some(transfer) = TransferCharacteristics::from_code(code) {
+                assert_eq!(transfer.code(), code);
+            }
+            if let Some(matrix) = MatrixCoefficients::from_code(code) {
+                assert_eq!(matrix.code(), code);
+            }
+        }
+
+        assert_eq!(ColorPrimaries::from_code(3), None);
+        assert_eq!(
+            TransferCharacteristics::from_code(16),
+            Some(TransferCharacteristics::Pq)
+        );
+        assert_eq!(
+            MatrixCoefficients::from_code(9),
+            Some(MatrixCoefficients::Bt2020Ncl)
+        );
+        assert_eq!(ChromaSiting::from_code(2), Some(ChromaSiting::TopLeft));
+        assert_eq!(Range::from_full_range_flag(false), Range::Limited);
+    }
+
+    #[test]
+    fn tagged_layouts() {
+        let layout = crate::Layout::<[u8; 4]>::width_and_height(2, 2).unwrap();
+        let canvas = Canvas::new(layout)
+            .mended(AlphaMode::Premultiplied)
+            .mended(ColorDescriptor::BT2100_HLG);
+
+        assert_eq!(canvas.layout().color(), ColorDescriptor::BT2100_HLG);
+        assert_eq!(canvas.as_slice().len(), 4);
+
+        let canvas: Canvas<AlphaLayout<_>> = canvas.decay();
+        assert_eq!(canvas.layout().mode(), AlphaMode::Premultiplied);
+    }
+}
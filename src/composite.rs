@@ -0,0 +1,382 @@
+//! Alpha compositing of RGBA pixels.
+//!
+//! A source matrix of RGBA pixels is blended onto a destination at some offset. The destination
+//! is any strided byte canvas with four channels of the same sample type as the source, in any
+//! channel order. In particular this includes the planes of DRM formats such as `ARGB8888`.
+//!
+//! Operators are evaluated on the region where source and destination overlap. Destination pixels
+//! outside of that region are never modified, even for operators such as [`Operator::In`] that
+//! would clear them if the source were extended with transparent pixels.
+//!
+//! ```
+//! use canvas::Matrix;
+//! use canvas::composite::{Composite, Operator};
+//!
+//! let mut frame = Matrix::<[u8; 4]>::with_width_and_height(64, 64);
+//! let mut overlay = Matrix::<[u8; 4]>::with_width_and_height(16, 16);
+//! overlay[(0, 0)] = [0xff, 0x00, 0x00, 0x80];
+//!
+//! Composite::new(Operator::Over).composite(&overlay, (&mut frame).into(), (8, 8));
+//! assert_eq!(frame[(8, 8)], [0xff, 0x00, 0x00, 0x80]);
+//! ```
+use core::convert::TryInto;
+
+pub use crate::alpha::AlphaMode;
+use crate::drm::FourCC;
+use crate::stride::ByteCanvasMut;
+use crate::Matrix;
+
+/// The compositing operator, determining how source and destination are combined.
+///
+/// The Porter-Duff operators determine the result purely from the coverage of each pixel, which
+/// is given by its alpha. The blend modes additionally mix the colors where both overlap and
+/// otherwise behave like `Over`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Operator {
+    /// The source is placed over the destination.
+    Over,
+    /// The part of the source lying inside of the destination.
+    In,
+    /// The part of the source lying outside of the destination.
+    Out,
+    /// The part of the source inside the destination, placed over the destination.
+    Atop,
+    /// The parts of source and destination lying outside of each other.
+    Xor,
+    /// Multiply the colors, which darkens the destination.
+    Multiply,
+    /// Multiply the complements of the colors, which lightens the destination.
+    Screen,
+}
+
+/// The position of each channel within a pixel of four channels.
+///
+/// This is always a permutation, each of the four positions holds exactly one channel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ChannelOrder {
+    red: u8,
+    green: u8,
+    blue: u8,
+    alpha: u8,
+}
+
+/// A channel type that can be composited.
+///
+/// Integer types are interpreted as normalized values, where the maximum value represents `1.0`.
+pub trait Channel: Copy {
+    /// Convert to a value in the unit range.
+    fn to_unit(self) -> f32;
+    /// Convert from a value in the unit range, rounding to the closest representable value.
+    fn from_unit(val: f32) -> Self;
+    /// Read a channel from native endian bytes.
+    fn from_bytes(bytes: &[u8]) -> Self;
+    /// Write a channel as native endian bytes.
+    fn write_bytes(self, bytes: &mut [u8]);
+}
+
+/// Describes how a source is composited onto a destination.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Composite {
+    /// The operator combining source and destination.
+    pub operator: Operator,
+    /// The alpha interpretation of both source and destination.
+    pub alpha: AlphaMode,
+    /// The channel order of the destination. The source is always ordered as RGBA.
+    pub order: ChannelOrder,
+}
+
+impl ChannelOrder {
+    /// Channels in the order red, green, blue and alpha.
+    pub const RGBA: Self = ChannelOrder::preset([0, 1, 2, 3]);
+    /// Channels in the order blue, green, red and alpha.
+    pub const BGRA: Self = ChannelOrder::preset([2, 1, 0, 3]);
+    /// Channels in the order alpha, red, green and blue.
+    pub const ARGB: Self = ChannelOrder::preset([1, 2, 3, 0]);
+    /// Channels in the order alpha, blue, green and red.
+    pub const ABGR: Self = ChannelOrder::preset([3, 2, 1, 0]);
+
+    /// Create an order from the positions of red, green, blue and alpha within a pixel.
+    ///
+    /// Returns `None` if a position is not below four or if two channels share a position.
+    pub const fn new(positions: [u8; 4]) -> Option<Self> {
+        let mut seen = [false; 4];
+        let mut idx = 0;
+        while idx < 4 {
+            let position = positions[idx] as usize;
+            if position >= 4 || seen[position] {
+                return None;
+            }
+            seen[position] = true;
+            idx += 1;
+        }
+
+        let [red, green, blue, alpha] = positions;
+        Some(ChannelOrder {
+            red,
+            green,
+            blue,
+            alpha,
+        })
+    }
+
+    const fn preset(positions: [u8; 4]) -> Self {
+        match ChannelOrder::new(positions) {
+            Some(order) => order,
+            None => panic!("Invalid channel order"),
+        }
+    }
+
+    /// The positions of red, green, blue and alpha within a pixel.
+    pub fn positions(self) -> [u8; 4] {
+        [self.red, self.green, self.blue, self.alpha]
+    }
+
+    /// The order of channels in memory for a DRM format with 8-bit alpha.
+    ///
+    /// DRM codes describe the channels of a little endian integer, from the most significant bits
+    /// to the least significant. For example, the bytes of `ARGB8888` are stored as blue, green,
+    /// red and alpha. Formats without alpha return `None`, as their padding channel can not be
+    /// treated as coverage.
+    pub fn from_fourcc(fourcc: FourCC) -> Option<Self> {
+        Some(match fourcc {
+            FourCC::ARGB8888 => ChannelOrder::BGRA,
+            FourCC::ABGR8888 => ChannelOrder::RGBA,
+            FourCC::RGBA8888 => ChannelOrder::ABGR,
+            FourCC::BGRA8888 => ChannelOrder::ARGB,
+            _ => return None,
+        })
+    }
+
+    fn indices(self) -> [usize; 4] {
+        self.positions().map(usize::from)
+    }
+}
+
+impl Composite {
+    /// Composite with straight alpha onto a destination with RGBA channel order.
+    pub fn new(operator: Operator) -> Self {
+        Composite {
+            operator,
+            alpha: AlphaMode::Straight,
+            order: ChannelOrder::RGBA,
+        }
+    }
+
+    /// Composite the source onto the destination, at an offset of `(x, y)` pixels.
+    ///
+    /// The offset may be negative or place the source partially outside of the destination, then
+    /// only the overlapping part is composited.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the element size of the destination is not that of the source
+    /// pixels.
+    pub fn composite<C: Channel>(
+        &self,
+        src: &Matrix<[C; 4]>,
+        mut dst: ByteCanvasMut<'_>,
+        (x, y): (isize, isize),
+    ) {
+        let size = core::mem::size_of::<C>();
+        let spec = dst.layout().spec();
+        assert_eq!(spec.element.size(), 4 * size, "Mismatching pixel size.");
+
+        let layout = src.layout();
+        let (xs, xd) = overlap(x, layout.width(), spec.width);
+        let (ys, yd) = overlap(y, layout.height(), spec.height);
+        let indices = self.order.indices();
+
+        for (sy, dy) in ys.zip(yd) {
+            for (sx, dx) in xs.clone().zip(xd.clone()) {
+                let source = src[(sx, sy)].map(C::to_unit);
+                let bytes = dst.element_bytes_mut(dx, dy);
+                let dest = indices.map(|idx| C::from_bytes(&bytes[idx * size..][..size]).to_unit());
+
+                let result = self.pixel(source, dest);
+                for (idx, val) in indices.iter().zip(&result) {
+                    C::from_unit(*val).write_bytes(&mut bytes[idx * size..][..size]);
+                }
+            }
+        }
+    }
+
+    /// Composite a single pixel, given as RGBA in the unit range.
+    fn pixel(&self, src: [f32; 4], dst: [f32; 4]) -> [f32; 4] {
+        let (src, dst) = match self.alpha {
+            AlphaMode::Straight => (premultiply(src), premultiply(dst)),
+            AlphaMode::Premultiplied => (src, dst),
+        };
+
+        let (sa, da) = (src[3], dst[3]);
+        let (fa, fb, alpha) = match self.operator {
+            Operator::Over | Operator::Multiply | Operator::Screen => {
+                (1.0, 1.0 - sa, sa + da - sa * da)
+            }
+            Operator::In => (da, 0.0, sa * da),
+            Operator::Out => (1.0 - da, 0.0, sa * (1.0 - da)),
+            Operator::Atop => (da, 1.0 - sa, da),
+            Operator::Xor => (1.0 - da, 1.0 - sa, sa + da - 2.0 * sa * da),
+        };
+
+        let mut result = [0.0, 0.0, 0.0, alpha];
+        for ((out, &s), &d) in result.iter_mut().zip(&src).zip(&dst).take(3) {
+            *out = match self.operator {
+                // The blended color replaces the source where both overlap.
+                Operator::Multiply => s * d + s * (1.0 - da) + d * (1.0 - sa),
+                Operator::Screen => s + d - s * d,
+                _ => s * fa + d * fb,
+            };
+        }
+
+        match self.alpha {
+            AlphaMode::Straight => unpremultiply(result),
+            AlphaMode::Premultiplied => result,
+        }
+    }
+}
+
+/// The ranges of source and destination coordinates along one axis that overlap.
+fn overlap(
+    offset: isize,
+    src_len: usize,
+    dst_len: usize,
+) -> (core::ops::Range<usize>, core::ops::Range<usize>) {
+    let skip = offset.min(0).unsigned_abs().min(src_len);
+    let start = offset.max(0) as usize;
+    let len = (src_len - skip).min(dst_len.saturating_sub(start));
+    (skip..skip + len, start..start + len)
+}
+
+fn premultiply([r, g, b, a]: [f32; 4]) -> [f32; 4] {
+    [r * a, g * a, b * a, a]
+}
+
+fn unpremultiply([r, g, b, a]: [f32; 4]) -> [f32; 4] {
+    if a > 0.0 {
+        [r / a, g / a, b / a, a]
+    } else {
+        [0.0; 4]
+    }
+}
+
+impl Channel for u8 {
+    fn to_unit(self) -> f32 {
+        f32::from(self) / 255.0
+    }
+
+    fn from_unit(val: f32) -> Self {
+        // Casts saturate, including negative values.
+        (val * 255.0 + 0.5) as u8
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        bytes[0]
+    }
+
+    fn write_bytes(self, bytes: &mut [u8]) {
+        bytes[0] = self;
+    }
+}
+
+impl Channel for f32 {
+    fn to_unit(self) -> f32 {
+        self
+    }
+
+    fn from_unit(val: f32) -> Self {
+        val
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        f32::from_ne_bytes(bytes.try_into().unwrap())
+    }
+
+    fn write_bytes(self, bytes: &mut [u8]) {
+        bytes.copy_from_slice(&self.to_ne_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pixels::F32;
+    use crate::Layout;
+    use alloc::vec::Vec;
+
+    fn rgba_f32(width: usize, height: usize, fill: [f32; 4]) -> Matrix<[f32; 4]> {
+        let layout = Layout::width_and_height_for_pixel(F32.array4(), width, height).unwrap();
+        let mut matrix = Matrix::with_layout(layout);
+        matrix.as_mut_slice().iter_mut().for_each(|p| *p = fill);
+        matrix
+    }
+
+    #[test]
+    fn porter_duff() {
+        let src = rgba_f32(1, 1, [1.0, 0.0, 0.0, 0.5]);
+        let check = |operator, expected: [f32; 4]| {
+            let mut dst = rgba_f32(1, 1, [0.0, 0.0, 0.5, 0.5]);
+            let composite = Composite {
+                alpha: AlphaMode::Premultiplied,
+                ..Composite::new(operator)
+            };
+            composite.composite(&src, (&mut dst).into(), (0, 0));
+            assert_eq!(dst[(0, 0)], expected, "{:?}", operator);
+        };
+
+        check(Operator::Over, [1.0, 0.0, 0.25, 0.75]);
+        check(Operator::In, [0.5, 0.0, 0.0, 0.25]);
+        check(Operator::Out, [0.5, 0.0, 0.0, 0.25]);
+        check(Operator::Atop, [0.5, 0.0, 0.25, 0.5]);
+        check(Operator::Xor, [0.5, 0.0, 0.25, 0.5]);
+        check(Operator::Multiply, [0.5, 0.0, 0.25, 0.75]);
+        check(Operator::Screen, [1.0, 0.0, 0.5, 0.75]);
+    }
+
+    #[test]
+    fn straight_over() {
+        let src = rgba_f32(1, 1, [1.0, 0.0, 0.0, 0.5]);
+        let mut dst = rgba_f32(1, 1, [0.0, 0.0, 1.0, 1.0]);
+        Composite::new(Operator::Over).composite(&src, (&mut dst).into(), (0, 0));
+        assert_eq!(dst[(0, 0)], [0.5, 0.0, 0.5, 1.0]);
+    }
+
+    #[test]
+    fn clipped_offset() {
+        let mut src = Matrix::<[u8; 4]>::with_width_and_height(2, 2);
+        src.as_mut_slice()
+            .iter_mut()
+            .for_each(|p| *p = [0xff, 0xff, 0xff, 0xff]);
+        let mut dst = Matrix::<[u8; 4]>::with_width_and_height(3, 3);
+
+        Composite::new(Operator::Over).composite(&src, (&mut dst).into(), (-1, 2));
+        let covered: Vec<_> = dst.as_slice().iter().map(|p| p[3] != 0).collect();
+        assert_eq!(
+            covered,
+            [false, false, false, false, false, false, true, false, false]
+        );
+    }
+
+    #[test]
+    fn drm_channel_order() {
+        let mut src = Matrix::<[u8; 4]>::with_width_and_height(1, 1);
+        src[(0, 0)] = [0x10, 0x20, 0x30, 0xff];
+        let mut dst = Matrix::<[u8; 4]>::with_width_and_height(1, 1);
+
+        let composite = Composite {
+            order: ChannelOrder::from_fourcc(FourCC::ARGB8888).unwrap(),
+            ..Composite::new(Operator::Over)
+        };
+        composite.composite(&src, (&mut dst).into(), (0, 0));
+        assert_eq!(dst[(0, 0)], [0x30, 0x20, 0x10, 0xff]);
+        assert_eq!(
+            u32::from_le_bytes(dst[(0, 0)]),
+            0xff102030,
+            "As an ARGB integer"
+        );
+
+        assert_eq!(ChannelOrder::new([2, 1, 0, 3]), Some(ChannelOrder::BGRA));
+        assert_eq!(ChannelOrder::ARGB.positions(), [1, 2, 3, 0]);
+        assert_eq!(ChannelOrder::new([0, 1, 2, 4]), None);
+        assert_eq!(ChannelOrder::new([0, 1, 1, 3]), None);
+    }
+}
@@ -13,6 +13,8 @@
 //! pixel matrix. Then some of those formats map cleanly to planes of color information that can be
 //! viewed as a matrix with strides, which finally enables useful operations such as
 //! initialization.
+use crate::bits::{BitLayout, BitOrder};
+use crate::layout::{Channels, SampleKind, Texel};
 use crate::{layout, pixel, stride};
 use core::convert::TryFrom;
 use core::fmt;
@@ -162,6 +164,7 @@ enum BadDrmKind {
     ZeroBlockHeight,
     OverlappingPlanes,
     UndescribableElement,
+    UnalignedPlane,
     IllegalPlaneWidth,
     IllegalPlaneHeight,
     LineSize,
@@ -174,6 +177,13 @@ enum BadDrmKind {
     IllegalVsub,
 }
 
+/// The bytes of a single block of a plane, such as its black value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BlockBytes {
+    bytes: [u8; 8],
+    len: u8,
+}
+
 /// An error converting an info into a supported layout.
 pub struct BadDrmError {
     kind: BadDrmKind,
@@ -274,8 +284,10 @@ impl DrmFormatInfo {
             return None;
         }
 
-        Some(match self.format {
+        let element: layout::Element = match self.format.little_endian() {
             FourCC::C8 | FourCC::RGB332 | FourCC::BGR332 => pixel::constants::U8.into(),
+            FourCC::C1 | FourCC::C2 | FourCC::C4 => pixel::constants::U8.into(),
+            FourCC::R1 | FourCC::R2 | FourCC::R4 => pixel::constants::U8.into(),
             FourCC::XRGB444
             | FourCC::XBGR444
             | FourCC::RGBX444
@@ -307,7 +319,9 @@ impl DrmFormatInfo {
             | FourCC::XBGR16161616F
             | FourCC::ARGB16161616F
             | FourCC::ABGR16161616F => pixel::constants::U16.array4().into(),
-            FourCC::YUYV | FourCC::YVYU | FourCC::AYUV => pixel::constants::U8.array4().into(),
+            FourCC::YUYV | FourCC::YVYU | FourCC::AYUV | FourCC::XYUV888 => {
+                pixel::constants::U8.array4().into()
+            }
             FourCC::VUY101010 => pixel::constants::U32.into(),
             FourCC::VUY888 => pixel::constants::U8.array3().into(),
             // Actually planar formats.
@@ -356,8 +370,180 @@ impl DrmFormatInfo {
             | FourCC::YVU444 => pixel::constants::U8.into(),
             // No element that fits (or not implemented?).
             _ => return None,
+        };
+
+        Some(match self.block_texel(plane) {
+            Some(texel) => element.with_texel(texel),
+            None => element,
         })
     }
+
+    /// The description of the channels of each block of a plane.
+    ///
+    /// The channels of packed formats are listed from the least significant bit, in the byte order
+    /// of the format. This is also the texel of the element returned by `block_element`. Formats
+    /// with less than one byte per pixel instead describe a single pixel of the block, see
+    /// [`DrmLayout::bit_layout`].
+    pub fn block_texel(self, plane: PlaneIdx) -> Option<Texel> {
+        if usize::from(self.num_planes) <= plane.to_index() {
+            return None;
+        }
+
+        let first = plane == PlaneIdx::First;
+        let unorm = |channels, bits| Texel::new(SampleKind::UNorm, channels, bits);
+        let bytes = |channels, count| Texel::uniform(SampleKind::UNorm, channels, count, 8);
+
+        Some(match self.format.little_endian() {
+            FourCC::C8 => Texel::uniform(SampleKind::UInt, Channels::Index, 1, 8),
+            FourCC::C1 => Texel::uniform(SampleKind::UInt, Channels::Index, 1, 1),
+            FourCC::C2 => Texel::uniform(SampleKind::UInt, Channels::Index, 1, 2),
+            FourCC::C4 => Texel::uniform(SampleKind::UInt, Channels::Index, 1, 4),
+            FourCC::R1 => unorm(Channels::Gray, [1, 0, 0, 0]),
+            FourCC::R2 => unorm(Channels::Gray, [2, 0, 0, 0]),
+            FourCC::R4 => unorm(Channels::Gray, [4, 0, 0, 0]),
+            FourCC::RGB332 => unorm(Channels::Bgr, [2, 3, 3, 0]),
+            FourCC::BGR332 => unorm(Channels::Rgb, [3, 3, 2, 0]),
+            FourCC::XRGB444 => unorm(Channels::Bgrx, [4; 4]),
+            FourCC::XBGR444 => unorm(Channels::Rgbx, [4; 4]),
+            FourCC::RGBX444 => unorm(Channels::Xbgr, [4; 4]),
+            FourCC::BGRX444 => unorm(Channels::Xrgb, [4; 4]),
+            FourCC::ARGB444 => unorm(Channels::Bgra, [4; 4]),
+            FourCC::ABGR444 => unorm(Channels::Rgba, [4; 4]),
+            FourCC::RGBA444 => unorm(Channels::Abgr, [4; 4]),
+            FourCC::BGRA444 => unorm(Channels::Argb, [4; 4]),
+            FourCC::RGB565 => unorm(Channels::Bgr, [5, 6, 5, 0]),
+            FourCC::BGR565 => unorm(Channels::Rgb, [5, 6, 5, 0]),
+            FourCC::RGB888 => bytes(Channels::Bgr, 3),
+            FourCC::BGR888 => bytes(Channels::Rgb, 3),
+            FourCC::XRGB8888 => bytes(Channels::Bgrx, 4),
+            FourCC::XBGR8888 => bytes(Channels::Rgbx, 4),
+            FourCC::RGBX8888 => bytes(Channels::Xbgr, 4),
+            FourCC::BGRX8888 => bytes(Channels::Xrgb, 4),
+            FourCC::ARGB8888 => bytes(Channels::Bgra, 4),
+            FourCC::ABGR8888 => bytes(Channels::Rgba, 4),
+            FourCC::RGBA8888 => bytes(Channels::Abgr, 4),
+            FourCC::BGRA8888 => bytes(Channels::Argb, 4),
+            FourCC::XRGB2101010 => unorm(Channels::Bgrx, [10, 10, 10, 2]),
+            FourCC::XBGR2101010 => unorm(Channels::Rgbx, [10, 10, 10, 2]),
+            FourCC::RGBX1010102 => unorm(Channels::Xbgr, [2, 10, 10, 10]),
+            FourCC::BGRX1010102 => unorm(Channels::Xrgb, [2, 10, 10, 10]),
+            FourCC::ARGB2101010 => unorm(Channels::Bgra, [10, 10, 10, 2]),
+            FourCC::ABGR2101010 => unorm(Channels::Rgba, [10, 10, 10, 2]),
+            FourCC::RGBA1010102 => unorm(Channels::Abgr, [2, 10, 10, 10]),
+            FourCC::BGRA1010102 => unorm(Channels::Argb, [2, 10, 10, 10]),
+            FourCC::XRGB16161616F => Texel::uniform(SampleKind::Float, Channels::Bgrx, 4, 16),
+            FourCC::XBGR16161616F => Texel::uniform(SampleKind::Float, Channels::Rgbx, 4, 16),
+            FourCC::ARGB16161616F => Texel::uniform(SampleKind::Float, Channels::Bgra, 4, 16),
+            FourCC::ABGR16161616F => Texel::uniform(SampleKind::Float, Channels::Rgba, 4, 16),
+            FourCC::YUYV => bytes(Channels::Yuyv, 4),
+            FourCC::YVYU => bytes(Channels::Yvyu, 4),
+            FourCC::AYUV => bytes(Channels::CrCbYA, 4),
+            FourCC::XYUV888 => bytes(Channels::CrCbYX, 4),
+            FourCC::VUY888 => bytes(Channels::YCbCr, 3),
+            FourCC::VUY101010 => unorm(Channels::YCbCr, [10, 10, 10, 0]),
+            FourCC::XRGB888_A8 if first => bytes(Channels::Bgrx, 4),
+            FourCC::XBGR888_A8 if first => bytes(Channels::Rgbx, 4),
+            FourCC::RGB888_A8 if first => bytes(Channels::Bgr, 3),
+            FourCC::BGR888_A8 if first => bytes(Channels::Rgb, 3),
+            FourCC::RGB565_A8 if first => unorm(Channels::Bgr, [5, 6, 5, 0]),
+            FourCC::BGR565_A8 if first => unorm(Channels::Rgb, [5, 6, 5, 0]),
+            FourCC::XRGB888_A8
+            | FourCC::XBGR888_A8
+            | FourCC::RGB888_A8
+            | FourCC::BGR888_A8
+            | FourCC::RGB565_A8
+            | FourCC::BGR565_A8 => bytes(Channels::Alpha, 1),
+            _ if self.is_yuv && first => bytes(Channels::Luma, 1),
+            FourCC::NV12 | FourCC::NV16 | FourCC::NV24 => bytes(Channels::CbCr, 2),
+            FourCC::NV21 | FourCC::NV61 | FourCC::NV42 => bytes(Channels::CrCb, 2),
+            FourCC::YUV410 | FourCC::YUV411 | FourCC::YUV420 | FourCC::YUV422 | FourCC::YUV444 => {
+                bytes(
+                    if plane == PlaneIdx::Second {
+                        Channels::Cb
+                    } else {
+                        Channels::Cr
+                    },
+                    1,
+                )
+            }
+            FourCC::YVU410 | FourCC::YVU411 | FourCC::YVU420 | FourCC::YVU422 | FourCC::YVU444 => {
+                bytes(
+                    if plane == PlaneIdx::Second {
+                        Channels::Cr
+                    } else {
+                        Channels::Cb
+                    },
+                    1,
+                )
+            }
+            _ => return None,
+        })
+    }
+}
+
+impl DrmFormatInfo {
+    /// The bytes of one opaque black block of a plane.
+    ///
+    /// For YUV formats this assumes the limited range usual for video, that is a luma of 16 and
+    /// chroma of 128, or 64 and 512 for the 10-bit channels of `VUY101010`. The bytes of big
+    /// endian formats are reversed, like their blocks. Returns `None` for planes outside the
+    /// format and where no element is known, just like `block_element`.
+    pub fn black(self, plane: PlaneIdx) -> Option<BlockBytes> {
+        let element = self.block_element(plane)?;
+        let little: &[u8] = match self.format.little_endian() {
+            FourCC::ARGB444 | FourCC::ABGR444 => &[0x00, 0xf0],
+            FourCC::RGBA444 | FourCC::BGRA444 => &[0x0f, 0x00],
+            FourCC::ARGB8888 | FourCC::ABGR8888 => &[0x00, 0x00, 0x00, 0xff],
+            FourCC::RGBA8888 | FourCC::BGRA8888 => &[0xff, 0x00, 0x00, 0x00],
+            FourCC::ARGB2101010 | FourCC::ABGR2101010 => &[0x00, 0x00, 0x00, 0xc0],
+            FourCC::RGBA1010102 | FourCC::BGRA1010102 => &[0x03, 0x00, 0x00, 0x00],
+            // An alpha of 1.0 as a half-precision float.
+            FourCC::ARGB16161616F | FourCC::ABGR16161616F => &[0, 0, 0, 0, 0, 0, 0x00, 0x3c],
+            FourCC::YUYV | FourCC::YVYU => &[16, 128, 16, 128],
+            FourCC::AYUV => &[128, 128, 16, 0xff],
+            FourCC::XYUV888 => &[128, 128, 16, 0],
+            FourCC::VUY888 => &[16, 128, 128],
+            FourCC::VUY101010 => &[0x40, 0x00, 0x08, 0x20],
+            FourCC::XRGB888_A8
+            | FourCC::XBGR888_A8
+            | FourCC::RGB888_A8
+            | FourCC::BGR888_A8
+            | FourCC::RGB565_A8
+            | FourCC::BGR565_A8
+                if plane == PlaneIdx::Second =>
+            {
+                &[0xff]
+            }
+            // Planar and semi-planar formats with 8-bit channels.
+            _ if self.is_yuv && plane == PlaneIdx::First && element.size() == 1 => &[16],
+            _ if self.is_yuv && element.size() <= 2 => &[128, 128][..element.size()],
+            _ if self.is_yuv => return None,
+            _ => &[0; 8][..element.size()],
+        };
+
+        let mut block = BlockBytes::new(little);
+        if self.format.is_big_endian() {
+            block.bytes[..little.len()].reverse();
+        }
+
+        Some(block)
+    }
+}
+
+impl BlockBytes {
+    fn new(bytes: &[u8]) -> Self {
+        let mut block = BlockBytes {
+            bytes: [0; 8],
+            len: bytes.len() as u8,
+        };
+        block.bytes[..bytes.len()].copy_from_slice(bytes);
+        block
+    }
+
+    /// The bytes of the block, as many as the size of the plane's element.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..usize::from(self.len)]
+    }
 }
 
 impl PlaneIdx {
@@ -418,10 +604,16 @@ impl DrmLayout {
                 return Err(BadDrmKind::OverlappingPlanes.into());
             }
 
-            format_info
+            let element = format_info
                 .block_element(plane)
                 .ok_or(BadDrmKind::UndescribableElement)?;
 
+            // Alignments are powers of two.
+            let align_mask = element.align() as u32 - 1;
+            if (info.offsets[idx] | info.pitches[idx]) & align_mask != 0 {
+                return Err(BadDrmKind::UnalignedPlane.into());
+            }
+
             let width = format_info
                 .plane_width(info.width, plane)
                 .ok_or(BadDrmKind::IllegalPlaneWidth)?;
@@ -483,6 +675,24 @@ impl DrmLayout {
         self.info.format.format
     }
 
+    /// Describe the pixels of a format with less than one byte per pixel.
+    ///
+    /// This applies to the color index formats `C1`, `C2` and `C4` as well as `R1`, `R2` and
+    /// `R4`. The layout covers the bytes of the first plane, starting at its offset, and retains
+    /// its pitch as row stride. Returns `None` for all other formats.
+    pub fn bit_layout(&self) -> Option<BitLayout> {
+        let bits = match self.fourcc().little_endian() {
+            FourCC::C1 | FourCC::R1 => 1,
+            FourCC::C2 | FourCC::R2 => 2,
+            FourCC::C4 | FourCC::R4 => 4,
+            _ => return None,
+        };
+
+        BitLayout::new(bits, BitOrder::MsbFirst, self.info.width, self.info.height)?
+            .with_row_stride(self.info.pitches[0] as usize)?
+            .with_offset(self.info.offsets[0] as usize)
+    }
+
     /// Get the layout of the nth plane of this frame buffer.
     pub fn plane(&self, plane_idx: PlaneIdx) -> Option<PlaneLayout> {
         let idx = plane_idx.to_index();
@@ -566,6 +776,20 @@ impl FourCC {
     pub const INVALID: Self = FourCC(0);
     /// Single 8 bpp grey color.
     pub const C8: Self = FourCC::from(*b"C8  ");
+    /// Color index with 1 bpp, eight pixels per byte starting at the most significant bit.
+    pub const C1: Self = FourCC::from(*b"C1  ");
+    /// Color index with 2 bpp, four pixels per byte starting at the most significant bits.
+    pub const C2: Self = FourCC::from(*b"C2  ");
+    /// Color index with 4 bpp, two pixels per byte starting at the most significant bits.
+    pub const C4: Self = FourCC::from(*b"C4  ");
+
+    /* sub-byte single channel */
+    /// Brightness with 1 bpp, eight pixels per byte starting at the most significant bit.
+    pub const R1: Self = FourCC::from(*b"R1  ");
+    /// Brightness with 2 bpp, four pixels per byte starting at the most significant bits.
+    pub const R2: Self = FourCC::from(*b"R2  ");
+    /// Brightness with 4 bpp, two pixels per byte starting at the most significant bits.
+    pub const R4: Self = FourCC::from(*b"R4  ");
 
     /* 8 bpp rgb */
     /// 8bpp rgb with 3 bits red, 3 bits green, 2 bits blue.
@@ -718,19 +942,62 @@ impl FourCC {
     /// Y+Cr+Cb with 8 bits each and not subsampled on chromatic planes.
     pub const YVU444: Self = FourCC::from(*b"YV24");
 
+    /// The flag marking a format as big endian, `DRM_FORMAT_BIG_ENDIAN`.
+    ///
+    /// Such a format has the same channels as the format without the flag but each block is
+    /// stored in big endian byte order instead.
+    pub const BIG_ENDIAN_FLAG: u32 = 1 << 31;
+
+    /// Check if the big endian flag is set.
+    pub const fn is_big_endian(self) -> bool {
+        self.0 & Self::BIG_ENDIAN_FLAG != 0
+    }
+
+    /// The same format, with its blocks stored in big endian byte order.
+    pub const fn big_endian(self) -> Self {
+        FourCC(self.0 | Self::BIG_ENDIAN_FLAG)
+    }
+
+    /// The same format, with its blocks stored in little endian byte order.
+    ///
+    /// This is the default and the form of all predefined codes.
+    pub const fn little_endian(self) -> Self {
+        FourCC(self.0 & !Self::BIG_ENDIAN_FLAG)
+    }
+
     const fn from(arr: [u8; 4]) -> Self {
         // FourCC(u32::from_le_bytes(arr)); not yet stable as const-fn
         FourCC(arr[0] as u32 | (arr[1] as u32) << 8 | (arr[2] as u32) << 16 | (arr[3] as u32) << 24)
     }
 
     /// Try to convert the format into an info.
+    ///
+    /// The big endian flag is supported for all formats and retained in the info.
     pub fn info(self) -> Result<DrmFormatInfo, BadDrmError> {
-        let mut info = match self {
+        let mut info = match self.little_endian() {
             FourCC::C8 => DrmFormatInfo {
                 num_planes: 1,
                 char_per_block: [1, 0, 0, 0],
                 ..DrmFormatInfo::PIXEL1_TEMPLATE
             },
+            FourCC::C1 | FourCC::R1 => DrmFormatInfo {
+                num_planes: 1,
+                char_per_block: [1, 0, 0, 0],
+                block_w: [8, 0, 0, 0],
+                ..DrmFormatInfo::PIXEL1_TEMPLATE
+            },
+            FourCC::C2 | FourCC::R2 => DrmFormatInfo {
+                num_planes: 1,
+                char_per_block: [1, 0, 0, 0],
+                block_w: [4, 0, 0, 0],
+                ..DrmFormatInfo::PIXEL1_TEMPLATE
+            },
+            FourCC::C4 | FourCC::R4 => DrmFormatInfo {
+                num_planes: 1,
+                char_per_block: [1, 0, 0, 0],
+                block_w: [2, 0, 0, 0],
+                ..DrmFormatInfo::PIXEL1_TEMPLATE
+            },
             FourCC::RGB332 | FourCC::BGR332 => DrmFormatInfo {
                 num_planes: 1,
                 char_per_block: [1, 0, 0, 0],
@@ -932,6 +1199,18 @@ impl From<BadDrmKind> for BadDrmError {
     }
 }
 
+impl From<u32> for FourCC {
+    fn from(code: u32) -> Self {
+        FourCC(code)
+    }
+}
+
+impl From<FourCC> for u32 {
+    fn from(fourcc: FourCC) -> Self {
+        fourcc.0
+    }
+}
+
 impl layout::Layout for DrmLayout {
     fn byte_len(&self) -> usize {
         self.total_len
@@ -947,11 +1226,15 @@ impl layout::Layout for PlaneLayout {
 impl stride::Strided for PlaneLayout {
     fn strided(&self) -> stride::StrideLayout {
         let element = self.element();
-        let width = self.width();
-        let height = self.height();
-        let matrix = layout::Matrix::from_width_height(element, width, height)
-            .expect("Fits in memory because the plane does");
-        stride::StrideLayout::with_row_major(matrix)
+        stride::StrideLayout::new(stride::StrideSpec {
+            width: self.width(),
+            height: self.height(),
+            element,
+            width_stride: element.size() as isize,
+            height_stride: self.pitch as isize,
+            offset: self.offset as usize,
+        })
+        .expect("Fits in memory and is aligned because the plane is valid")
     }
 }
 
@@ -1043,3 +1326,309 @@ fn yuv_planes() {
     assert_eq!(second.height, 300);
     assert_eq!(second.element.size(), 2);
 }
+
+#[test]
+fn clear_planes() {
+    use crate::canvas::Canvas;
+    use stride::Strided;
+
+    let info = FourCC::NV12.info().expect("Has info for");
+    let layout = info.as_layout(3, 3).expect("Compile to a small layout");
+    let mut canvas = Canvas::new(layout);
+
+    for &plane in &PlaneIdx::PLANES[..2] {
+        let strided = canvas.layout().plane(plane).unwrap().strided();
+        let black = info.black(plane).unwrap();
+        stride::ByteCanvasMut::with_bytes(strided, canvas.as_bytes_mut())
+            .expect("Plane within buffer")
+            .fill(black.as_bytes());
+    }
+
+    assert_eq!(
+        canvas.as_bytes(),
+        &[16, 16, 16, 16, 16, 16, 16, 16, 16, 128, 128, 128, 128, 128, 128, 128, 128]
+    );
+    assert!(info.black(PlaneIdx::Third).is_none());
+    let ayuv = FourCC::AYUV.info().unwrap().black(PlaneIdx::First).unwrap();
+    assert_eq!(ayuv.as_bytes(), &[128, 128, 16, 0xff]);
+}
+
+#[test]
+fn black_fills_elements() {
+    let formats = [
+        FourCC::C8,
+        FourCC::C1,
+        FourCC::C2,
+        FourCC::C4,
+        FourCC::R1,
+        FourCC::R2,
+        FourCC::R4,
+        FourCC::RGB332,
+        FourCC::BGR332,
+        FourCC::XRGB444,
+        FourCC::XBGR444,
+        FourCC::RGBX444,
+        FourCC::BGRX444,
+        FourCC::ARGB444,
+        FourCC::ABGR444,
+        FourCC::RGBA444,
+        FourCC::BGRA444,
+        FourCC::RGB565,
+        FourCC::BGR565,
+        FourCC::RGB888,
+        FourCC::BGR888,
+        FourCC::XRGB8888,
+        FourCC::XBGR8888,
+        FourCC::RGBX8888,
+        FourCC::BGRX8888,
+        FourCC::ARGB8888,
+        FourCC::ABGR8888,
+        FourCC::RGBA8888,
+        FourCC::BGRA8888,
+        FourCC::XRGB2101010,
+        FourCC::XBGR2101010,
+        FourCC::RGBX1010102,
+        FourCC::BGRX1010102,
+        FourCC::ARGB2101010,
+        FourCC::ABGR2101010,
+        FourCC::RGBA1010102,
+        FourCC::BGRA1010102,
+        FourCC::XRGB16161616F,
+        FourCC::XBGR16161616F,
+        FourCC::ARGB16161616F,
+        FourCC::ABGR16161616F,
+        FourCC::YUYV,
+        FourCC::YVYU,
+        FourCC::AYUV,
+        FourCC::XYUV888,
+        FourCC::VUY888,
+        FourCC::VUY101010,
+        FourCC::XRGB888_A8,
+        FourCC::XBGR888_A8,
+        FourCC::RGB888_A8,
+        FourCC::BGR888_A8,
+        FourCC::RGB565_A8,
+        FourCC::BGR565_A8,
+        FourCC::NV12,
+        FourCC::NV21,
+        FourCC::NV16,
+        FourCC::NV61,
+        FourCC::NV24,
+        FourCC::NV42,
+        FourCC::YUV410,
+        FourCC::YVU410,
+        FourCC::YUV411,
+        FourCC::YVU411,
+        FourCC::YUV420,
+        FourCC::YVU420,
+        FourCC::YUV422,
+        FourCC::YVU422,
+        FourCC::YUV444,
+        FourCC::YVU444,
+    ];
+
+    for &format in &formats {
+        let info = format.info().expect("Has info for");
+        for &plane in &PlaneIdx::PLANES {
+            let element = info.block_element(plane);
+            let black = info.black(plane);
+            assert_eq!(
+                black.map(|black| black.as_bytes().len()),
+                element.map(layout::Element::size),
+                "{:?} {:?}",
+                format,
+                plane
+            );
+        }
+    }
+
+    let xyuv = FourCC::XYUV888
+        .info()
+        .unwrap()
+        .black(PlaneIdx::First)
+        .unwrap();
+    assert_eq!(xyuv.as_bytes(), &[128, 128, 16, 0]);
+}
+
+#[test]
+fn big_endian_formats() {
+    use stride::Strided;
+
+    let big = FourCC::RGB565.big_endian();
+    assert!(big.is_big_endian());
+    assert_eq!(big.little_endian(), FourCC::RGB565);
+    assert_eq!(
+        u32::from(big),
+        u32::from(FourCC::RGB565) | FourCC::BIG_ENDIAN_FLAG
+    );
+
+    let info = big.info().expect("Has info for");
+    assert_eq!(info.format, big);
+    assert!(info.block_element(PlaneIdx::First).is_some());
+    let black = info.black(PlaneIdx::First).unwrap();
+    assert_eq!(black.as_bytes(), &[0, 0]);
+
+    let layout = info.as_layout(4, 4).expect("Compile to 4x4 layout");
+    assert_eq!(layout.fourcc(), big);
+    assert_eq!(layout.plane(PlaneIdx::First).unwrap().fourcc(), big);
+
+    // Clear a big endian plane, the opaque alpha byte now comes first.
+    let big = FourCC::ARGB8888.big_endian().info().unwrap();
+    let black = big.black(PlaneIdx::First).unwrap();
+    assert_eq!(black.as_bytes(), &[0xff, 0, 0, 0]);
+
+    let xrgb = FourCC::XRGB8888.big_endian().info().unwrap();
+    let layout = xrgb.as_layout(2, 1).unwrap();
+    let strided = layout.plane(PlaneIdx::First).unwrap().strided();
+    let mut bytes = [0xaa; 8];
+    let black = xrgb.black(PlaneIdx::First).unwrap();
+    stride::ByteCanvasMut::with_bytes(strided, &mut bytes)
+        .unwrap()
+        .fill(black.as_bytes());
+    assert_eq!(bytes, [0; 8]);
+
+    let half = FourCC::ABGR16161616F.big_endian().info().unwrap();
+    let black = half.black(PlaneIdx::First).unwrap();
+    assert_eq!(black.as_bytes(), &[0x3c, 0, 0, 0, 0, 0, 0, 0]);
+}
+
+#[test]
+fn texels() {
+    let info = FourCC::ARGB2101010.info().expect("Has info for");
+    let element = info.block_element(PlaneIdx::First).unwrap();
+    let texel = element.texel().expect("Has a texel");
+    assert_eq!(texel.channels, Channels::Bgra);
+    assert_eq!(texel.kind, SampleKind::UNorm);
+    assert_eq!(texel.alpha_position(), Some(3));
+    assert_eq!(texel.bits_per_texel() as usize, 8 * element.size());
+
+    let info = FourCC::YVU420.info().expect("Has info for");
+    let texel = info.block_texel(PlaneIdx::Second).unwrap();
+    assert_eq!(texel.channels, Channels::Cr);
+    assert_eq!(texel.channel_count(), 1);
+
+    let texel = layout::Element::from_pixel::<[u8; 4]>().texel().unwrap();
+    assert_eq!(texel.channels, Channels::Rgba);
+    assert_eq!(texel.alpha_position(), Some(3));
+
+    let plane = info
+        .as_layout(4, 4)
+        .unwrap()
+        .plane(PlaneIdx::First)
+        .unwrap();
+    use stride::Strided;
+    let texel = plane.strided().spec().element.texel().unwrap();
+    assert_eq!(texel.channels, Channels::Luma);
+}
+
+#[test]
+fn unaligned_planes() {
+    let request = |pitch, offset| DrmFramebufferCmd {
+        width: 4,
+        height: 2,
+        fourcc: FourCC::RGB565,
+        flags: 0,
+        pitches: [pitch, 0, 0, 0],
+        offsets: [offset, 0, 0, 0],
+        modifier: [0; 4],
+    };
+
+    let unaligned = |pitch, offset| {
+        let result = DrmLayout::new(&request(pitch, offset));
+        matches!(
+            result,
+            Err(BadDrmError {
+                kind: BadDrmKind::UnalignedPlane
+            })
+        )
+    };
+
+    assert!(unaligned(8, 1));
+    assert!(unaligned(9, 0));
+    assert!(DrmLayout::new(&request(10, 2)).is_ok());
+}
+
+#[test]
+fn strided_planes() {
+    use stride::Strided;
+
+    let request = DrmFramebufferCmd {
+        width: 4,
+        height: 2,
+        fourcc: FourCC::RGB565,
+        flags: 0,
+        pitches: [10, 0, 0, 0],
+        offsets: [2, 0, 0, 0],
+        modifier: [0; 4],
+    };
+
+    let layout = DrmLayout::new(&request).unwrap();
+    let strided = layout.plane(PlaneIdx::First).unwrap().strided();
+    let spec = strided.spec();
+    assert_eq!(
+        (spec.offset, spec.width_stride, spec.height_stride),
+        (2, 2, 10)
+    );
+    assert_eq!(layout::Layout::byte_len(&strided), 20);
+
+    let mut bytes = [0; 20];
+    let mut canvas = stride::ByteCanvasMut::with_bytes(strided, &mut bytes).unwrap();
+    let rect = layout::Rect {
+        x: 1,
+        y: 1,
+        width: 1,
+        height: 1,
+    };
+    canvas.fill_rect(rect, &[0xab, 0xcd]);
+    assert_eq!(bytes[14..16], [0xab, 0xcd]);
+    assert_eq!(bytes.iter().filter(|&&b| b != 0).count(), 2);
+}
+
+#[test]
+fn sub_byte_formats() {
+    use crate::bits::BitMatrix;
+    use crate::layout::Coord;
+
+    let info = FourCC::C2.info().expect("Has info for");
+    let layout = info.as_layout(5, 3).unwrap();
+    assert_eq!(layout.plane(PlaneIdx::First).unwrap().pitch, 2);
+
+    let bits = layout.bit_layout().unwrap();
+    assert_eq!((bits.bits(), bits.row_stride()), (2, 2));
+    assert_eq!(bits.order(), BitOrder::MsbFirst);
+
+    let mut image = BitMatrix::new(bits);
+    image.set(Coord(4, 2), 3);
+    assert_eq!(image.as_bytes(), &[0, 0, 0, 0, 0, 0xc0]);
+
+    // The plane starts after a header, in the same buffer.
+    let request = DrmFramebufferCmd {
+        width: 5,
+        height: 3,
+        fourcc: FourCC::C2,
+        flags: 0,
+        pitches: [3, 0, 0, 0],
+        offsets: [4, 0, 0, 0],
+        modifier: [0; 4],
+    };
+    let bits = DrmLayout::new(&request).unwrap().bit_layout().unwrap();
+    assert_eq!((bits.offset(), bits.row_stride()), (4, 3));
+
+    let mut image = BitMatrix::new(bits);
+    image.set(Coord(0, 0), 1);
+    image.set(Coord(4, 2), 3);
+    assert_eq!(
+        image.as_bytes(),
+        &[0, 0, 0, 0, 0x40, 0, 0, 0, 0, 0, 0, 0xc0, 0]
+    );
+
+    let texel = info.block_texel(PlaneIdx::First).unwrap();
+    assert_eq!((texel.channels, texel.bits[0]), (Channels::Index, 2));
+    assert!(FourCC::XRGB8888
+        .info()
+        .unwrap()
+        .as_layout(1, 1)
+        .unwrap()
+        .bit_layout()
+        .is_none());
+}
@@ -0,0 +1,159 @@
+//! Samples with an explicit byte order.
+//!
+//! Image data exchanged with files, the network or other devices often has a byte order that
+//! differs from the native one. The wrappers in this module store a value in a fixed byte order
+//! regardless of the target so that a matrix of them has the same bytes on every platform.
+//!
+//! ```
+//! use canvas::Matrix;
+//! use canvas::endian::BigEndian;
+//!
+//! let matrix = Matrix::<u16>::with_width_and_height(1, 1);
+//! let matrix = matrix.map(|_| BigEndian::new(0x1234u16));
+//! assert_eq!(matrix.as_bytes(), &[0x12, 0x34]);
+//! assert_eq!(matrix[(0, 0)].get(), 0x1234);
+//! ```
+
+/// A value stored in big endian byte order.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct BigEndian<T>(T);
+
+/// A value stored in little endian byte order.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct LittleEndian<T>(T);
+
+/// A sample whose bytes can be reversed.
+///
+/// This is implemented for all primitive numbers and arrays of them, where each array element is
+/// reversed individually.
+pub trait SwapBytes: Copy {
+    /// Reverse the byte order of the sample.
+    #[must_use]
+    fn swap_bytes(self) -> Self;
+}
+
+impl<T: SwapBytes> BigEndian<T> {
+    /// Store a native value in big endian byte order.
+    pub fn new(value: T) -> Self {
+        BigEndian(to_big(value))
+    }
+
+    /// Retrieve the native value.
+    pub fn get(self) -> T {
+        to_big(self.0)
+    }
+}
+
+impl<T: SwapBytes> LittleEndian<T> {
+    /// Store a native value in little endian byte order.
+    pub fn new(value: T) -> Self {
+        LittleEndian(to_little(value))
+    }
+
+    /// Retrieve the native value.
+    pub fn get(self) -> T {
+        to_little(self.0)
+    }
+}
+
+/// Convert between native and big endian, this is its own inverse.
+fn to_big<T: SwapBytes>(value: T) -> T {
+    if cfg!(target_endian = "big") {
+        value
+    } else {
+        value.swap_bytes()
+    }
+}
+
+/// Convert between native and little endian, this is its own inverse.
+fn to_little<T: SwapBytes>(value: T) -> T {
+    if cfg!(target_endian = "little") {
+        value
+    } else {
+        value.swap_bytes()
+    }
+}
+
+macro_rules! swap_integer {
+    ($($ty:ty),*) => {
+        $(impl SwapBytes for $ty {
+            fn swap_bytes(self) -> Self {
+                <$ty>::swap_bytes(self)
+            }
+        })*
+    };
+}
+
+swap_integer!(u8, i8, u16, i16, u32, i32, u64, i64);
+
+impl SwapBytes for f32 {
+    fn swap_bytes(self) -> Self {
+        f32::from_bits(self.to_bits().swap_bytes())
+    }
+}
+
+impl SwapBytes for f64 {
+    fn swap_bytes(self) -> Self {
+        f64::from_bits(self.to_bits().swap_bytes())
+    }
+}
+
+impl<T: SwapBytes, const N: usize> SwapBytes for [T; N] {
+    fn swap_bytes(self) -> Self {
+        self.map(T::swap_bytes)
+    }
+}
+
+impl<T: SwapBytes + core::fmt::Debug> core::fmt::Debug for BigEndian<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_tuple("BigEndian").field(&self.get()).finish()
+    }
+}
+
+impl<T: SwapBytes + core::fmt::Debug> core::fmt::Debug for LittleEndian<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_tuple("LittleEndian").field(&self.get()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pixels::{BE_F32, BE_U32, LE_U16};
+    use crate::{Matrix, Rec};
+
+    #[test]
+    fn byte_order() {
+        let big = BigEndian::new(0x1234_5678u32);
+        assert_eq!(BE_U32.cast_to_bytes(&[big]), &[0x12, 0x34, 0x56, 0x78]);
+        assert_eq!(big.get(), 0x1234_5678);
+
+        let little = LittleEndian::new(0x1234u16);
+        assert_eq!(LE_U16.cast_to_bytes(&[little]), &[0x34, 0x12]);
+        assert_eq!(little.get(), 0x1234);
+
+        let float = BigEndian::new(1.0f32);
+        assert_eq!(BE_F32.cast_to_bytes(&[float]), &[0x3f, 0x80, 0x00, 0x00]);
+        assert_eq!(float.get(), 1.0);
+    }
+
+    #[test]
+    fn bulk_swap() {
+        let mut rec = Rec::<u32>::new(2);
+        rec.as_mut_slice()[1] = 0x1234_5678;
+        rec.swap_bytes();
+        assert_eq!(rec.as_slice(), &[0, 0x7856_3412]);
+
+        let mut matrix = Matrix::<[u8; 4]>::with_width_and_height(1, 1);
+        matrix[(0, 0)] = [1, 2, 3, 4];
+        // Single bytes have no order to swap.
+        matrix.swap_bytes();
+        assert_eq!(matrix[(0, 0)], [1, 2, 3, 4]);
+
+        let mut matrix = matrix.transmute::<u32>();
+        matrix.swap_bytes();
+        assert_eq!(matrix.as_bytes(), &[4, 3, 2, 1]);
+    }
+}
@@ -0,0 +1,429 @@
+//! Checksums of image content that ignore padding.
+//!
+//! Strided and DRM layouts may contain bytes that are not part of any pixel, such as the padding
+//! at the end of each row. Their value is arbitrary and hashing them would make checksums of
+//! otherwise equal frames differ. The digests here visit only the elements described by a layout,
+//! row by row, in the order in which they would be stored in a tightly packed image.
+//!
+//! The MD5 of a frame is computed over its tightly packed planes, one after another. This is the
+//! digest that `ffmpeg -f framemd5` reports for raw video and [`FrameDigest::framemd5_line`]
+//! formats it in the same way. Each plane additionally has its own MD5 and the common CRC-32 of
+//! zlib and IEEE 802.3, as used by V4L2 compliance tools.
+//!
+//! ```
+//! use canvas::Matrix;
+//! use canvas::hash::{FrameDigest, PlaneDigest};
+//! use canvas::stride::ByteCanvasRef;
+//!
+//! let mut matrix = Matrix::<u8>::with_width_and_height(4, 2);
+//! matrix.as_mut_slice().copy_from_slice(b"abcdefgh");
+//!
+//! let plane = PlaneDigest::new(ByteCanvasRef::from(&matrix));
+//! assert_eq!(plane.md5.to_string(), "e8dc4081b13434b45189a720b77b6818");
+//! assert_eq!(plane.crc32, 0xaeef2a50);
+//!
+//! let frame = FrameDigest::new(vec![ByteCanvasRef::from(&matrix)]);
+//! assert_eq!(
+//!     frame.framemd5_line(0, 0, 1),
+//!     "0,          0,          0,        1,        8, e8dc4081b13434b45189a720b77b6818",
+//! );
+//! ```
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::drm::{DrmLayout, PlaneIdx};
+use crate::stride::{ByteCanvasRef, Strided};
+
+/// An incremental MD5 hasher.
+///
+/// MD5 is not a cryptographically secure hash. It is only provided to match existing reference
+/// checksums.
+#[derive(Clone)]
+pub struct Md5 {
+    state: [u32; 4],
+    block: [u8; 64],
+    len: u64,
+}
+
+/// The result of an MD5 hash.
+///
+/// Displays as lower-case hexadecimal digits, as printed by `md5sum` and `ffmpeg`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Md5Digest(pub [u8; 16]);
+
+/// An incremental CRC-32 hasher.
+///
+/// This is the CRC with the reflected polynomial `0xedb88320` used by zlib, PNG and Ethernet.
+#[derive(Clone, Copy, Debug)]
+pub struct Crc32 {
+    value: u32,
+}
+
+/// The checksums of the elements of a single plane.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PlaneDigest {
+    /// The MD5 of the tightly packed elements.
+    pub md5: Md5Digest,
+    /// The CRC-32 of the tightly packed elements.
+    pub crc32: u32,
+    /// The number of hashed bytes.
+    pub size: usize,
+}
+
+/// The checksums of a frame and each of its planes.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FrameDigest {
+    md5: Md5Digest,
+    size: usize,
+    planes: Vec<PlaneDigest>,
+}
+
+impl Md5 {
+    const SHIFTS: [u32; 16] = [7, 12, 17, 22, 5, 9, 14, 20, 4, 11, 16, 23, 6, 10, 15, 21];
+
+    // The integer part of `abs(sin(i + 1)) * 2^32`.
+    const SINES: [u32; 64] = [
+        0xd76aa478, 0xe8c7b756, 0x242070db, 0xc1bdceee, 0xf57c0faf, 0x4787c62a, 0xa8304613,
+        0xfd469501, 0x698098d8, 0x8b44f7af, 0xffff5bb1, 0x895cd7be, 0x6b901122, 0xfd987193,
+        0xa679438e, 0x49b40821, 0xf61e2562, 0xc040b340, 0x265e5a51, 0xe9b6c7aa, 0xd62f105d,
+        0x02441453, 0xd8a1e681, 0xe7d3fbc8, 0x21e1cde6, 0xc33707d6, 0xf4d50d87, 0x455a14ed,
+        0xa9e3e905, 0xfcefa3f8, 0x676f02d9, 0x8d2a4c8a, 0xfffa3942, 0x8771f681, 0x6d9d6122,
+        0xfde5380c, 0xa4beea44, 0x4bdecfa9, 0xf6bb4b60, 0xbebfbc70, 0x289b7ec6, 0xeaa127fa,
+        0xd4ef3085, 0x04881d05, 0xd9d4d039, 0xe6db99e5, 0x1fa27cf8, 0xc4ac5665, 0xf4292244,
+        0x432aff97, 0xab9423a7, 0xfc93a039, 0x655b59c3, 0x8f0ccc92, 0xffeff47d, 0x85845dd1,
+        0x6fa87e4f, 0xfe2ce6e0, 0xa3014314, 0x4e0811a1, 0xf7537e82, 0xbd3af235, 0x2ad7d2bb,
+        0xeb86d391,
+    ];
+
+    /// Start a new hash.
+    pub fn new() -> Self {
+        Md5 {
+            state: [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476],
+            block: [0; 64],
+            len: 0,
+        }
+    }
+
+    /// Append bytes to the hashed message.
+    pub fn update(&mut self, mut bytes: &[u8]) {
+        let mut filled = (self.len % 64) as usize;
+        self.len = self.len.wrapping_add(bytes.len() as u64);
+
+        while !bytes.is_empty() {
+            let take = (64 - filled).min(bytes.len());
+            self.block[filled..][..take].copy_from_slice(&bytes[..take]);
+            bytes = &bytes[take..];
+            filled += take;
+
+            if filled == 64 {
+                let block = self.block;
+                self.compress(&block);
+                filled = 0;
+            }
+        }
+    }
+
+    /// Finish the message and get its digest.
+    pub fn finish(mut self) -> Md5Digest {
+        let bits = self.len.wrapping_mul(8);
+        let filled = (self.len % 64) as usize;
+        let padding = if filled < 56 {
+            56 - filled
+        } else {
+            120 - filled
+        };
+
+        let mut tail = [0u8; 72];
+        tail[0] = 0x80;
+        tail[padding..][..8].copy_from_slice(&bits.to_le_bytes());
+        self.update(&tail[..padding + 8]);
+
+        let mut digest = [0; 16];
+        for (chunk, word) in digest.chunks_exact_mut(4).zip(&self.state) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        Md5Digest(digest)
+    }
+
+    fn compress(&mut self, block: &[u8; 64]) {
+        let mut words = [0u32; 16];
+        for (word, chunk) in words.iter_mut().zip(block.chunks_exact(4)) {
+            *word = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        }
+
+        let [mut a, mut b, mut c, mut d] = self.state;
+        for i in 0..64 {
+            let (f, g) = match i / 16 {
+                0 => ((b & c) | (!b & d), i),
+                1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+                2 => (b ^ c ^ d, (3 * i + 5) % 16),
+                _ => (c ^ (b | !d), (7 * i) % 16),
+            };
+
+            let shift = Self::SHIFTS[i / 16 * 4 + i % 4];
+            let sum = a
+                .wrapping_add(f)
+                .wrapping_add(Self::SINES[i])
+                .wrapping_add(words[g]);
+            a = d;
+            d = c;
+            c = b;
+            b = b.wrapping_add(sum.rotate_left(shift));
+        }
+
+        for (state, value) in self.state.iter_mut().zip(&[a, b, c, d]) {
+            *state = state.wrapping_add(*value);
+        }
+    }
+}
+
+impl Crc32 {
+    const TABLE: [u32; 256] = {
+        let mut table = [0; 256];
+        let mut idx = 0;
+        while idx < 256 {
+            let mut value = idx as u32;
+            let mut bit = 0;
+            while bit < 8 {
+                value = if value & 1 == 1 {
+                    (value >> 1) ^ 0xedb88320
+                } else {
+                    value >> 1
+                };
+                bit += 1;
+            }
+            table[idx] = value;
+            idx += 1;
+        }
+        table
+    };
+
+    /// Start a new checksum.
+    pub fn new() -> Self {
+        Crc32 { value: !0 }
+    }
+
+    /// Append bytes to the checksummed message.
+    pub fn update(&mut self, bytes: &[u8]) {
+        self.value = bytes.iter().fold(self.value, |crc, &byte| {
+            Self::TABLE[usize::from(crc as u8 ^ byte)] ^ (crc >> 8)
+        });
+    }
+
+    /// Get the checksum of the message so far.
+    pub fn finish(self) -> u32 {
+        !self.value
+    }
+}
+
+impl PlaneDigest {
+    /// Hash the elements of a strided canvas.
+    pub fn new(canvas: ByteCanvasRef<'_>) -> Self {
+        let (mut md5, mut crc32, mut size) = (Md5::new(), Crc32::new(), 0);
+        canvas.visit_bytes(|bytes| {
+            md5.update(bytes);
+            crc32.update(bytes);
+            size += bytes.len();
+        });
+
+        PlaneDigest {
+            md5: md5.finish(),
+            crc32: crc32.finish(),
+            size,
+        }
+    }
+
+    /// Hash the elements of a layout, such as a `StrideLayout` or a DRM `PlaneLayout`.
+    ///
+    /// The bytes are the whole buffer, the layout determines the offset of the elements within.
+    /// Returns `None` if the buffer is too short for the layout.
+    pub fn with_layout(layout: &impl Strided, bytes: &[u8]) -> Option<Self> {
+        let canvas = ByteCanvasRef::with_bytes(layout.strided(), bytes)?;
+        Some(PlaneDigest::new(canvas))
+    }
+}
+
+impl FrameDigest {
+    /// Hash a frame consisting of the given planes, in order.
+    pub fn new<'data>(planes: impl IntoIterator<Item = ByteCanvasRef<'data>>) -> Self {
+        let mut md5 = Md5::new();
+        let planes = planes
+            .into_iter()
+            .map(|canvas| {
+                canvas.visit_bytes(|bytes| md5.update(bytes));
+                PlaneDigest::new(canvas)
+            })
+            .collect::<Vec<_>>();
+
+        FrameDigest {
+            md5: md5.finish(),
+            size: planes.iter().map(|plane| plane.size).sum(),
+            planes,
+        }
+    }
+
+    /// Hash all planes of a DRM frame buffer.
+    ///
+    /// Returns `None` if the buffer is too short for the layout.
+    pub fn with_drm(layout: &DrmLayout, bytes: &[u8]) -> Option<Self> {
+        let mut planes = Vec::new();
+        for &idx in &[PlaneIdx::First, PlaneIdx::Second, PlaneIdx::Third] {
+            if let Some(plane) = layout.plane(idx) {
+                planes.push(ByteCanvasRef::with_bytes(plane.strided(), bytes)?);
+            }
+        }
+
+        Some(FrameDigest::new(planes))
+    }
+
+    /// The MD5 of all planes, tightly packed one after another.
+    pub fn md5(&self) -> Md5Digest {
+        self.md5
+    }
+
+    /// The number of hashed bytes of all planes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The digests of the individual planes.
+    pub fn planes(&self) -> &[PlaneDigest] {
+        &self.planes
+    }
+
+    /// Format a frame line of the `framemd5` muxer of `ffmpeg`.
+    ///
+    /// The time stamps and duration are in units of the time base of the stream. For raw video
+    /// the decoding and presentation time stamps are the same.
+    pub fn framemd5_line(&self, stream: u32, pts: i64, duration: i64) -> String {
+        alloc::format!(
+            "{}, {:10}, {:10}, {:8}, {:8}, {}",
+            stream,
+            pts,
+            pts,
+            duration,
+            self.size,
+            self.md5
+        )
+    }
+}
+
+impl Default for Md5 {
+    fn default() -> Self {
+        Md5::new()
+    }
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Crc32::new()
+    }
+}
+
+impl fmt::Display for Md5Digest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.iter().try_for_each(|byte| write!(f, "{:02x}", byte))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drm::{DrmFramebufferCmd, FourCC};
+    use crate::layout::Element;
+    use crate::pixel::constants::U8;
+    use crate::stride::{StrideLayout, StrideSpec};
+    use alloc::string::ToString;
+
+    fn md5(bytes: &[u8]) -> String {
+        let mut hash = Md5::new();
+        hash.update(bytes);
+        hash.finish().to_string()
+    }
+
+    #[test]
+    fn reference_vectors() {
+        assert_eq!(md5(b""), "d41d8cd98f00b204e9800998ecf8427e");
+        assert_eq!(md5(b"abc"), "900150983cd24fb0d6963f7d28e17f72");
+        let long =
+            b"12345678901234567890123456789012345678901234567890123456789012345678901234567890";
+        assert_eq!(md5(long), "57edf4a22be3c955ac49da2e2107b67a");
+
+        let mut split = Md5::new();
+        long.chunks(7).for_each(|chunk| split.update(chunk));
+        assert_eq!(split.finish().to_string(), md5(long));
+
+        let mut crc = Crc32::new();
+        crc.update(b"123456789");
+        assert_eq!(crc.finish(), 0xcbf43926);
+    }
+
+    #[test]
+    fn padding_is_ignored() {
+        let packed = b"abcdef";
+        let spec = StrideSpec {
+            width: 3,
+            height: 2,
+            element: Element::from(U8),
+            width_stride: 1,
+            height_stride: 5,
+            offset: 2,
+        };
+        let padded = StrideLayout::new(spec).unwrap();
+        let data = b"..abc..def";
+
+        let expected = PlaneDigest::with_layout(&padded, data).unwrap();
+        assert_eq!(expected.md5.to_string(), md5(packed));
+        assert_eq!(expected.size, 6);
+
+        // A bottom-up image with the same rows hashes equal, rows are always visited top-down.
+        let flipped = StrideLayout::new(StrideSpec {
+            height_stride: -4,
+            offset: 4,
+            ..spec
+        })
+        .unwrap();
+        let digest = PlaneDigest::with_layout(&flipped, b"def abc").unwrap();
+        assert_eq!(digest, expected);
+
+        assert!(PlaneDigest::with_layout(&padded, b"..abc").is_none());
+    }
+
+    #[test]
+    fn drm_frames() {
+        let mut cmd = DrmFramebufferCmd {
+            width: 4,
+            height: 2,
+            fourcc: FourCC::NV12,
+            flags: 0,
+            pitches: [8, 8, 0, 0],
+            offsets: [0, 16, 0, 0],
+            modifier: [0; 4],
+        };
+        let layout = DrmLayout::new(&cmd).unwrap();
+        let mut bytes = [0xffu8; 24];
+        bytes[0..4].copy_from_slice(b"yyyy");
+        bytes[8..12].copy_from_slice(b"YYYY");
+        bytes[16..20].copy_from_slice(b"uvUV");
+
+        let frame = FrameDigest::with_drm(&layout, &bytes).unwrap();
+        assert_eq!(frame.size(), 12);
+        assert_eq!(frame.md5().to_string(), md5(b"yyyyYYYYuvUV"));
+        assert_eq!(frame.planes().len(), 2);
+        assert_eq!(frame.planes()[1].md5.to_string(), md5(b"uvUV"));
+
+        // Changing the padding does not change any digest.
+        bytes[4..8].copy_from_slice(b"pppp");
+        assert_eq!(FrameDigest::with_drm(&layout, &bytes).unwrap(), frame);
+
+        cmd.pitches = [4, 4, 0, 0];
+        cmd.offsets = [0, 8, 0, 0];
+        let packed = DrmLayout::new(&cmd).unwrap();
+        assert_eq!(
+            FrameDigest::with_drm(&packed, b"yyyyYYYYuvUV").unwrap(),
+            frame
+        );
+        assert!(FrameDigest::with_drm(&layout, &bytes[..19]).is_none());
+    }
+}
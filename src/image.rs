@@ -0,0 +1,300 @@
+//! Conversions with the buffers of the `image` crate.
+//!
+//! Matrices of sample arrays convert to and from an `ImageBuffer` of the corresponding color type,
+//! e.g. `Matrix<[u8; 4]>` and `ImageBuffer<Rgba<u8>, Vec<u8>>`. These conversions copy the samples
+//! once as the buffer of a matrix has a larger alignment than a `Vec` of samples, so neither
+//! allocation can be reused for the other. Borrowing an `ImageBuffer` as a strided byte canvas
+//! does not copy at all.
+//!
+//! A `DynamicImage` converts to a `Canvas<DynLayout>` whose element carries a [`Texel`] describing
+//! the color type, and back if the texel matches one of the image's color types.
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+use ::image::{DynamicImage, ImageBuffer, Luma, LumaA, Rgb, Rgba};
+
+use crate::layout::{self, Channels, DynLayout, Element, LayoutRepr, MismatchedPixelError};
+use crate::layout::{SampleKind, Texel};
+use crate::pixel::constants::{F32, U16, U8};
+use crate::stride::{ByteCanvasMut, ByteCanvasRef, StrideLayout};
+use crate::{Canvas, Layout, Matrix};
+
+macro_rules! image_buffers {
+    ($(($color:ident, $sample:ty, $n:literal, $pixel:expr, $channels:ident, $kind:ident)),*) => {
+        $(
+            impl From<&'_ ImageBuffer<$color<$sample>, Vec<$sample>>> for Matrix<[$sample; $n]> {
+                fn from(buffer: &ImageBuffer<$color<$sample>, Vec<$sample>>) -> Self {
+                    let (width, height) = buffer.dimensions();
+                    let layout =
+                        Layout::width_and_height_for_pixel($pixel, width as usize, height as usize)
+                            .expect("Layout of an existing image");
+                    let mut matrix = Matrix::with_layout(layout);
+                    let samples = &buffer.as_raw()[..matrix.as_slice().len() * $n];
+                    matrix
+                        .as_bytes_mut()
+                        .copy_from_slice(bytemuck::cast_slice(samples));
+                    matrix
+                }
+            }
+
+            impl From<ImageBuffer<$color<$sample>, Vec<$sample>>> for Matrix<[$sample; $n]> {
+                fn from(buffer: ImageBuffer<$color<$sample>, Vec<$sample>>) -> Self {
+                    Matrix::from(&buffer)
+                }
+            }
+
+            impl From<&'_ Matrix<[$sample; $n]>> for ImageBuffer<$color<$sample>, Vec<$sample>> {
+                fn from(matrix: &Matrix<[$sample; $n]>) -> Self {
+                    let layout = matrix.layout();
+                    let samples: &[$sample] = bytemuck::cast_slice(matrix.as_slice());
+                    ImageBuffer::from_raw(
+                        u32::try_from(layout.width()).expect("Width of an image"),
+                        u32::try_from(layout.height()).expect("Height of an image"),
+                        samples.to_vec(),
+                    )
+                    .expect("Buffer fits the dimensions")
+                }
+            }
+
+            impl From<Matrix<[$sample; $n]>> for ImageBuffer<$color<$sample>, Vec<$sample>> {
+                fn from(matrix: Matrix<[$sample; $n]>) -> Self {
+                    ImageBuffer::from(&matrix)
+                }
+            }
+
+            impl<'data> From<&'data ImageBuffer<$color<$sample>, Vec<$sample>>>
+                for ByteCanvasRef<'data>
+            {
+                fn from(buffer: &'data ImageBuffer<$color<$sample>, Vec<$sample>>) -> Self {
+                    let element = element($pixel.into(), SampleKind::$kind, Channels::$channels);
+                    let layout = strided(element, buffer.dimensions());
+                    ByteCanvasRef::with_bytes(layout, bytemuck::cast_slice(buffer.as_raw()))
+                        .expect("Buffer fits the dimensions")
+                }
+            }
+
+            impl<'data> From<&'data mut ImageBuffer<$color<$sample>, Vec<$sample>>>
+                for ByteCanvasMut<'data>
+            {
+                fn from(buffer: &'data mut ImageBuffer<$color<$sample>, Vec<$sample>>) -> Self {
+                    let element = element($pixel.into(), SampleKind::$kind, Channels::$channels);
+                    let layout = strided(element, buffer.dimensions());
+                    let samples: &mut [$sample] = &mut *buffer;
+                    ByteCanvasMut::with_bytes(layout, bytemuck::cast_slice_mut(samples))
+                        .expect("Buffer fits the dimensions")
+                }
+            }
+        )*
+    };
+}
+
+image_buffers!(
+    (Luma, u8, 1, U8.array1(), Gray, UNorm),
+    (LumaA, u8, 2, U8.array2(), GrayAlpha, UNorm),
+    (Rgb, u8, 3, U8.array3(), Rgb, UNorm),
+    (Rgba, u8, 4, U8.array4(), Rgba, UNorm),
+    (Luma, u16, 1, U16.array1(), Gray, UNorm),
+    (LumaA, u16, 2, U16.array2(), GrayAlpha, UNorm),
+    (Rgb, u16, 3, U16.array3(), Rgb, UNorm),
+    (Rgba, u16, 4, U16.array4(), Rgba, UNorm),
+    (Luma, f32, 1, F32.array1(), Gray, Float),
+    (LumaA, f32, 2, F32.array2(), GrayAlpha, Float),
+    (Rgb, f32, 3, F32.array3(), Rgb, Float),
+    (Rgba, f32, 4, F32.array4(), Rgba, Float)
+);
+
+/// Describe an element of uniform channels.
+fn element(element: Element, kind: SampleKind, channels: Channels) -> Element {
+    let count = match channels {
+        Channels::Gray => 1,
+        Channels::GrayAlpha => 2,
+        Channels::Rgb => 3,
+        _ => 4,
+    };
+    let bits = (8 * element.size() / count) as u8;
+    element.with_texel(Texel::uniform(kind, channels, count as u8, bits))
+}
+
+fn strided(element: Element, (width, height): (u32, u32)) -> StrideLayout {
+    let matrix = layout::Matrix::from_width_height(element, width as usize, height as usize)
+        .expect("Layout of an existing image");
+    StrideLayout::with_row_major(matrix)
+}
+
+/// Copy the samples of a buffer into a canvas with a described element.
+fn dyn_canvas<P: ::image::Pixel>(
+    buffer: &ImageBuffer<P, Vec<P::Subpixel>>,
+    kind: SampleKind,
+    channels: Channels,
+) -> Canvas<DynLayout>
+where
+    P::Subpixel: bytemuck::Pod,
+{
+    let size = core::mem::size_of::<P::Subpixel>() * usize::from(P::CHANNEL_COUNT);
+    let align = core::mem::align_of::<P::Subpixel>();
+    let bare = core::alloc::Layout::from_size_align(size, align).unwrap();
+    let element = element(Element::with_layout(bare).unwrap(), kind, channels);
+
+    let (width, height) = buffer.dimensions();
+    let matrix = layout::Matrix::from_width_height(element, width as usize, height as usize)
+        .expect("Layout of an existing image");
+    Canvas::with_bytes(
+        DynLayout::from(matrix),
+        bytemuck::cast_slice(buffer.as_raw()),
+    )
+}
+
+impl From<&'_ DynamicImage> for Canvas<DynLayout> {
+    /// Copy the samples of the image.
+    ///
+    /// Color types without a corresponding texel are converted to `Rgba` of `f32` first.
+    fn from(image: &DynamicImage) -> Self {
+        use SampleKind::{Float, UNorm};
+        match image {
+            DynamicImage::ImageLuma8(buffer) => dyn_canvas(buffer, UNorm, Channels::Gray),
+            DynamicImage::ImageLumaA8(buffer) => dyn_canvas(buffer, UNorm, Channels::GrayAlpha),
+            DynamicImage::ImageRgb8(buffer) => dyn_canvas(buffer, UNorm, Channels::Rgb),
+            DynamicImage::ImageRgba8(buffer) => dyn_canvas(buffer, UNorm, Channels::Rgba),
+            DynamicImage::ImageLuma16(buffer) => dyn_canvas(buffer, UNorm, Channels::Gray),
+            DynamicImage::ImageLumaA16(buffer) => dyn_canvas(buffer, UNorm, Channels::GrayAlpha),
+            DynamicImage::ImageRgb16(buffer) => dyn_canvas(buffer, UNorm, Channels::Rgb),
+            DynamicImage::ImageRgba16(buffer) => dyn_canvas(buffer, UNorm, Channels::Rgba),
+            DynamicImage::ImageRgb32F(buffer) => dyn_canvas(buffer, Float, Channels::Rgb),
+            DynamicImage::ImageRgba32F(buffer) => dyn_canvas(buffer, Float, Channels::Rgba),
+            other => dyn_canvas(&other.to_rgba32f(), Float, Channels::Rgba),
+        }
+    }
+}
+
+impl From<DynamicImage> for Canvas<DynLayout> {
+    fn from(image: DynamicImage) -> Self {
+        Canvas::from(&image)
+    }
+}
+
+impl TryFrom<&'_ Canvas<DynLayout>> for DynamicImage {
+    type Error = MismatchedPixelError;
+
+    /// Copy the samples of a canvas whose element describes one of the image color types.
+    fn try_from(canvas: &Canvas<DynLayout>) -> Result<Self, Self::Error> {
+        let matrix = match canvas.layout().repr {
+            LayoutRepr::Matrix(matrix) => matrix,
+            _ => return Err(MismatchedPixelError::default()),
+        };
+
+        let texel = matrix
+            .element()
+            .texel()
+            .ok_or_else(MismatchedPixelError::default)?;
+        let width = u32::try_from(matrix.width()).map_err(|_| MismatchedPixelError::default())?;
+        let height = u32::try_from(matrix.height()).map_err(|_| MismatchedPixelError::default())?;
+        let bytes = &canvas.as_bytes()[..matrix.byte_len()];
+
+        fn buffer<P: ::image::Pixel>(
+            (width, height): (u32, u32),
+            bytes: &[u8],
+        ) -> ImageBuffer<P, Vec<P::Subpixel>>
+        where
+            P::Subpixel: bytemuck::Pod,
+        {
+            let samples = bytemuck::cast_slice(bytes).to_vec();
+            ImageBuffer::from_raw(width, height, samples).expect("Buffer fits the dimensions")
+        }
+
+        let dims = (width, height);
+        let bits = texel.bits;
+        Ok(match (texel.kind, texel.channels) {
+            (SampleKind::UNorm, Channels::Gray) if bits == [8, 0, 0, 0] => {
+                DynamicImage::ImageLuma8(buffer(dims, bytes))
+            }
+            (SampleKind::UNorm, Channels::GrayAlpha) if bits == [8, 8, 0, 0] => {
+                DynamicImage::ImageLumaA8(buffer(dims, bytes))
+            }
+            (SampleKind::UNorm, Channels::Rgb) if bits == [8, 8, 8, 0] => {
+                DynamicImage::ImageRgb8(buffer(dims, bytes))
+            }
+            (SampleKind::UNorm, Channels::Rgba) if bits == [8; 4] => {
+                DynamicImage::ImageRgba8(buffer(dims, bytes))
+            }
+            (SampleKind::UNorm, Channels::Gray) if bits == [16, 0, 0, 0] => {
+                DynamicImage::ImageLuma16(buffer(dims, bytes))
+            }
+            (SampleKind::UNorm, Channels::GrayAlpha) if bits == [16, 16, 0, 0] => {
+                DynamicImage::ImageLumaA16(buffer(dims, bytes))
+            }
+            (SampleKind::UNorm, Channels::Rgb) if bits == [16, 16, 16, 0] => {
+                DynamicImage::ImageRgb16(buffer(dims, bytes))
+            }
+            (SampleKind::UNorm, Channels::Rgba) if bits == [16; 4] => {
+                DynamicImage::ImageRgba16(buffer(dims, bytes))
+            }
+            (SampleKind::Float, Channels::Rgb) if bits == [32, 32, 32, 0] => {
+                DynamicImage::ImageRgb32F(buffer(dims, bytes))
+            }
+            (SampleKind::Float, Channels::Rgba) if bits == [32; 4] => {
+                DynamicImage::ImageRgba32F(buffer(dims, bytes))
+            }
+            _ => return Err(MismatchedPixelError::default()),
+        })
+    }
+}
+
+impl TryFrom<Canvas<DynLayout>> for DynamicImage {
+    type Error = MismatchedPixelError;
+
+    fn try_from(canvas: Canvas<DynLayout>) -> Result<Self, Self::Error> {
+        DynamicImage::try_from(&canvas)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buffers() {
+        let mut buffer = ImageBuffer::<Rgba<u8>, Vec<u8>>::new(3, 2);
+        buffer.put_pixel(2, 1, Rgba([1, 2, 3, 4]));
+
+        let matrix = Matrix::<[u8; 4]>::from(&buffer);
+        assert_eq!(matrix[(2, 1)], [1, 2, 3, 4]);
+        assert_eq!(ImageBuffer::from(&matrix), buffer);
+
+        let canvas = ByteCanvasRef::from(&buffer);
+        let texel = canvas.layout().spec().element.texel().unwrap();
+        assert_eq!(texel.channels, Channels::Rgba);
+        assert_eq!(canvas.element_bytes(2, 1), &[1, 2, 3, 4]);
+
+        let mut buffer = ImageBuffer::<LumaA<f32>, Vec<f32>>::new(2, 2);
+        ByteCanvasMut::from(&mut buffer).fill(bytemuck::bytes_of(&[0.5f32, 1.0]));
+        let matrix = Matrix::<[f32; 2]>::from(buffer);
+        assert_eq!(matrix[(1, 1)], [0.5, 1.0]);
+    }
+
+    #[test]
+    fn dynamic_images() {
+        let mut buffer = ImageBuffer::<Rgb<u16>, Vec<u16>>::new(2, 3);
+        buffer.put_pixel(1, 2, Rgb([0xffff, 0x1234, 0]));
+        let image = DynamicImage::ImageRgb16(buffer);
+
+        let canvas = Canvas::<DynLayout>::from(&image);
+        let texel = match canvas.layout().repr {
+            LayoutRepr::Matrix(matrix) => matrix.element().texel().unwrap(),
+            _ => unreachable!(),
+        };
+        assert_eq!(texel.bits, [16, 16, 16, 0]);
+        assert_eq!(DynamicImage::try_from(&canvas).unwrap(), image);
+
+        let element = layout::Element::from_pixel::<[u8; 4]>();
+        let matrix = layout::Matrix::from_width_height(element, 1, 1).unwrap();
+        let canvas = Canvas::new(DynLayout::from(matrix));
+        assert!(matches!(
+            DynamicImage::try_from(canvas),
+            Ok(DynamicImage::ImageRgba8(_))
+        ));
+
+        let unknown = layout::Matrix::from_width_height(U16.into(), 1, 1).unwrap();
+        let canvas = Canvas::new(DynLayout::from(unknown));
+        assert!(DynamicImage::try_from(canvas).is_err());
+    }
+}
@@ -2,7 +2,9 @@
 use crate::pixel::MaxAligned;
 use crate::{AsPixel, Pixel};
 use ::alloc::boxed::Box;
-use core::{alloc, cmp};
+use core::{alloc, cmp, hash};
+
+pub use crate::texel::{Channels, SampleKind, Texel};
 
 /// A byte layout that only describes the user bytes.
 ///
@@ -21,10 +23,14 @@ pub struct Bytes(pub usize);
 /// This type is a lower semi lattice. That is, given two elements the type formed by taking the
 /// minimum of size and alignment individually will always form another valid element. This
 /// operation is implemented in the [`infimum`] method.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Ord, Hash)]
+///
+/// An element may additionally carry a [`Texel`] that describes its channels. This is not
+/// considered when comparing elements, which only concerns their size and alignment.
+#[derive(Clone, Copy, Debug)]
 pub struct Element {
     size: usize,
     align: usize,
+    texel: Option<Texel>,
 }
 
 /// A descriptor of the layout of image bytes.
@@ -32,7 +38,8 @@ pub struct Element {
 /// There is no color space and no strict type interpretation here, just some mapping to required
 /// bytes for such a fixed buffer and a width and height of the described image. This means that
 /// the byte usage for a particular buffer needs to be independent of the content, in particular
-/// can not be based on compressibility.
+/// can not be based on compressibility. A color space can be attached separately, see the
+/// `color` module.
 ///
 /// There is one more thing that differentiates an image from an encoded format. It is expected
 /// that the image can be unfolded into some matrix of independent pixels (with potentially
@@ -201,6 +208,19 @@ impl Coord {
     }
 }
 
+/// Describes a rectangle of image coordinates.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Rect {
+    /// The horizontal coordinate of the top-left corner.
+    pub x: u32,
+    /// The vertical coordinate of the top-left corner.
+    pub y: u32,
+    /// The number of pixels in width direction.
+    pub width: u32,
+    /// The number of pixels in height direction.
+    pub height: u32,
+}
+
 /// A layout that is a slice of samples.
 ///
 /// These layouts are represented with a slice of a _single_ type of samples. In particular these
@@ -252,6 +272,56 @@ pub struct Yuv420p {
     height: u32,
 }
 
+/// A matrix of elements grouped into rectangular tiles.
+///
+/// The tiles are stored row-major, from left to right and then from top to bottom, and the
+/// elements within each tile are also stored row-major. Tiles at the right and bottom edge are
+/// padded to the full tile size if the dimensions are not a multiple of the tile dimensions.
+///
+/// ```
+/// use canvas::layout::{Element, Layout, Tiled};
+/// use canvas::pixels::U8;
+///
+/// let tiled = Tiled::new(Element::from(U8), 20, 10, 16, 8).unwrap();
+/// assert_eq!(tiled.tiles_per_row(), 2);
+/// assert_eq!(tiled.byte_len(), 4 * 128);
+/// // The second tile starts after the first 128 bytes.
+/// assert_eq!(tiled.offset(17, 1), Some(128 + 16 + 1));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Tiled {
+    element: Element,
+    width: usize,
+    height: usize,
+    tile_width: usize,
+    tile_height: usize,
+}
+
+/// A matrix of elements stored in Morton order, also called Z-order.
+///
+/// The bits of the two coordinates are interleaved to form the index of an element, so that
+/// elements close to each other in both directions are mostly close in memory as well. Each
+/// dimension is padded to the next power of two. For rectangular images the Z-order covers
+/// squares of the smaller padded dimension which are then stored one after another.
+///
+/// ```
+/// use canvas::layout::{Coord, Element, Morton};
+/// use canvas::pixels::U8;
+///
+/// let morton = Morton::new(Element::from(U8), 3, 3).unwrap();
+/// assert_eq!((morton.padded_width(), morton.padded_height()), (4, 4));
+/// assert_eq!(morton.index(Coord(1, 0)), Some(1));
+/// assert_eq!(morton.index(Coord(0, 1)), Some(2));
+/// assert_eq!(morton.index(Coord(2, 2)), Some(12));
+/// assert_eq!(morton.index(Coord(3, 0)), None);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Morton {
+    element: Element,
+    width: u32,
+    height: u32,
+}
+
 /// A typed matrix of packed pixels (channel groups).
 ///
 /// This is a strongly-typed equivalent to [`Matrix`]. See it for details.
@@ -281,11 +351,14 @@ impl Bytes {
 
 impl Element {
     /// Construct an element from a self-evident pixel.
+    ///
+    /// The element is described by the texel of the pixel type, if it has one.
     pub fn from_pixel<P: AsPixel>() -> Self {
         let pix = P::pixel();
         Element {
             size: pix.size(),
             align: pix.align(),
+            texel: P::texel(),
         }
     }
 
@@ -297,6 +370,7 @@ impl Element {
         Element {
             size: isize::MAX as usize,
             align: 1,
+            texel: None,
         }
     };
 
@@ -317,6 +391,7 @@ impl Element {
         Some(Element {
             size: layout.size(),
             align: layout.align(),
+            texel: None,
         })
     }
 
@@ -342,6 +417,8 @@ impl Element {
     }
 
     /// Create an element having the smaller of both sizes and alignments.
+    ///
+    /// The texel is only kept if both elements have the same one.
     #[must_use = "This does not modify `self`."]
     pub fn infimum(self, other: Self) -> Element {
         // We still have size divisible by align. Whatever the smaller of both, it's divisible by
@@ -349,9 +426,30 @@ impl Element {
         Element {
             size: self.size.min(other.size),
             align: self.align.min(other.align),
+            texel: if self.texel == other.texel {
+                self.texel
+            } else {
+                None
+            },
         }
     }
 
+    /// Describe the channels of the element.
+    ///
+    /// This is not checked against the size of the element.
+    #[must_use = "This does not modify `self`."]
+    pub fn with_texel(self, texel: Texel) -> Element {
+        Element {
+            texel: Some(texel),
+            ..self
+        }
+    }
+
+    /// Get the description of the channels, if it is known.
+    pub const fn texel(self) -> Option<Texel> {
+        self.texel
+    }
+
     /// Get the size of the element.
     pub const fn size(self) -> usize {
         self.size
@@ -359,7 +457,7 @@ impl Element {
 
     /// Get the minimum required alignment of the element.
     pub const fn align(self) -> usize {
-        self.size
+        self.align
     }
 }
 
@@ -387,7 +485,11 @@ impl Matrix {
         second_dim: usize,
     ) -> Option<Self> {
         let max_index = first_dim.checked_mul(second_dim)?;
-        let _ = max_index.checked_mul(element.size)?;
+        let byte_len = max_index.checked_mul(element.size)?;
+        // No allocation can be larger, this also keeps all byte offsets expressible as `isize`.
+        if byte_len > isize::MAX as usize {
+            return None;
+        }
 
         Some(Matrix {
             element,
@@ -471,6 +573,9 @@ impl<P> TMatrix<P> {
         }
     }
 
+    /// Remove the pixel type, like `From` and `Decay`.
+    ///
+    /// The element has no texel as the pixel type is not known to be `AsPixel`.
     pub fn into_matrix(self) -> Matrix {
         Matrix {
             element: self.pixel.into(),
@@ -509,6 +614,227 @@ impl Yuv420p {
     }
 }
 
+impl Tiled {
+    /// Create a layout with the given dimensions of the image and of each tile.
+    ///
+    /// Returns `None` if a tile dimension is zero or if the padded layout does not fit into
+    /// memory.
+    pub fn new(
+        element: Element,
+        width: usize,
+        height: usize,
+        tile_width: usize,
+        tile_height: usize,
+    ) -> Option<Self> {
+        if tile_width == 0 || tile_height == 0 {
+            return None;
+        }
+
+        let tiles = width
+            .div_ceil(tile_width)
+            .checked_mul(height.div_ceil(tile_height))?;
+        let tile_len = tile_width
+            .checked_mul(tile_height)?
+            .checked_mul(element.size)?;
+        // No allocation can be larger, this also keeps all byte offsets expressible as `isize`.
+        if tiles.checked_mul(tile_len)? > isize::MAX as usize {
+            return None;
+        }
+
+        Some(Tiled {
+            element,
+            width,
+            height,
+            tile_width,
+            tile_height,
+        })
+    }
+
+    /// Get the element type of this layout.
+    pub const fn element(&self) -> Element {
+        self.element
+    }
+
+    /// Get the width in elements.
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Get the height in elements.
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Get the width of each tile in elements.
+    pub const fn tile_width(&self) -> usize {
+        self.tile_width
+    }
+
+    /// Get the height of each tile in elements.
+    pub const fn tile_height(&self) -> usize {
+        self.tile_height
+    }
+
+    /// The number of tiles in each row of tiles, including a partial one.
+    pub const fn tiles_per_row(&self) -> usize {
+        self.width.div_ceil(self.tile_width)
+    }
+
+    /// The number of tiles in each column of tiles, including a partial one.
+    pub const fn tiles_per_column(&self) -> usize {
+        self.height.div_ceil(self.tile_height)
+    }
+
+    /// The number of bytes of each tile, including its padding.
+    pub const fn tile_byte_len(&self) -> usize {
+        self.tile_width * self.tile_height * self.element.size
+    }
+
+    /// Get the required bytes for this layout, including the padding of partial tiles.
+    pub const fn byte_len(&self) -> usize {
+        // Does not overflow due to construction.
+        self.tiles_per_row() * self.tiles_per_column() * self.tile_byte_len()
+    }
+
+    /// The byte offset of the element at a coordinate.
+    ///
+    /// Returns `None` if the coordinate is outside the image.
+    pub fn offset(&self, x: usize, y: usize) -> Option<usize> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        let (tile_x, within_x) = (x / self.tile_width, x % self.tile_width);
+        let (tile_y, within_y) = (y / self.tile_height, y % self.tile_height);
+        let tile = tile_y * self.tiles_per_row() + tile_x;
+        let within = within_y * self.tile_width + within_x;
+        Some(tile * self.tile_byte_len() + within * self.element.size)
+    }
+
+    /// The strided layout of the elements of one tile, excluding its padding.
+    ///
+    /// The layout addresses the bytes of the whole tiled buffer. Returns `None` if the tile is
+    /// outside the image.
+    pub fn tile_layout(&self, tile_x: usize, tile_y: usize) -> Option<crate::stride::StrideLayout> {
+        if tile_x >= self.tiles_per_row() || tile_y >= self.tiles_per_column() {
+            return None;
+        }
+
+        let (x, y) = (tile_x * self.tile_width, tile_y * self.tile_height);
+        let spec = crate::stride::StrideSpec {
+            width: self.tile_width.min(self.width - x),
+            height: self.tile_height.min(self.height - y),
+            element: self.element,
+            width_stride: self.element.size as isize,
+            height_stride: (self.tile_width * self.element.size) as isize,
+            offset: self.offset(x, y)?,
+        };
+
+        let layout = crate::stride::StrideLayout::new(spec);
+        Some(layout.expect("Fits in memory and is aligned because the tiled layout is valid"))
+    }
+}
+
+impl Morton {
+    /// Create a layout for an image of the given dimensions.
+    ///
+    /// Returns `None` if the padded layout does not fit into memory.
+    pub fn new(element: Element, width: u32, height: u32) -> Option<Self> {
+        use core::convert::TryFrom;
+        let morton = Morton {
+            element,
+            width,
+            height,
+        };
+
+        let padded_width = usize::try_from(morton.padded_width()).ok()?;
+        let padded_height = usize::try_from(morton.padded_height()).ok()?;
+        let byte_len = padded_width
+            .checked_mul(padded_height)?
+            .checked_mul(element.size)?;
+        // No allocation can be larger, this also keeps all byte offsets expressible as `isize`.
+        if byte_len > isize::MAX as usize {
+            return None;
+        }
+
+        Some(morton)
+    }
+
+    /// Get the element type of this layout.
+    pub const fn element(&self) -> Element {
+        self.element
+    }
+
+    /// Get the width in elements.
+    pub const fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Get the height in elements.
+    pub const fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The width rounded up to a power of two.
+    pub const fn padded_width(&self) -> u64 {
+        (self.width as u64).next_power_of_two()
+    }
+
+    /// The height rounded up to a power of two.
+    pub const fn padded_height(&self) -> u64 {
+        (self.height as u64).next_power_of_two()
+    }
+
+    /// Get the required bytes for this layout, including padding.
+    pub const fn byte_len(&self) -> usize {
+        if self.width == 0 || self.height == 0 {
+            return 0;
+        }
+
+        // Does not overflow due to construction.
+        (self.padded_width() * self.padded_height()) as usize * self.element.size
+    }
+
+    /// The index of the element at a coordinate, counted in elements.
+    ///
+    /// Returns `None` if the coordinate is outside the image.
+    pub fn index(&self, Coord(x, y): Coord) -> Option<usize> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        // The number of bits that are interleaved, those of the smaller padded dimension.
+        let bits = self
+            .padded_width()
+            .min(self.padded_height())
+            .trailing_zeros();
+        let low = (1u32 << bits).wrapping_sub(1);
+        let morton = Self::spread(x & low) | Self::spread(y & low) << 1;
+        // Only the larger dimension has more bits, the other coordinate is shifted to zero.
+        let high = u64::from((x >> bits) | (y >> bits)) << (2 * bits);
+
+        // Does not overflow, the index is within the padded layout.
+        Some((high | morton) as usize)
+    }
+
+    /// The byte offset of the element at a coordinate.
+    ///
+    /// Returns `None` if the coordinate is outside the image.
+    pub fn offset(&self, coord: Coord) -> Option<usize> {
+        Some(self.index(coord)? * self.element.size)
+    }
+
+    /// Insert a zero bit above each bit of the value.
+    const fn spread(value: u32) -> u64 {
+        let mut v = value as u64;
+        v = (v | v << 16) & 0x0000_ffff_0000_ffff;
+        v = (v | v << 8) & 0x00ff_00ff_00ff_00ff;
+        v = (v | v << 4) & 0x0f0f_0f0f_0f0f_0f0f;
+        v = (v | v << 2) & 0x3333_3333_3333_3333;
+        (v | v << 1) & 0x5555_5555_5555_5555
+    }
+}
+
 impl Layout for Bytes {
     fn byte_len(&self) -> usize {
         self.0
@@ -521,6 +847,18 @@ impl Take for Bytes {
     }
 }
 
+impl Layout for Tiled {
+    fn byte_len(&self) -> usize {
+        Tiled::byte_len(self)
+    }
+}
+
+impl Layout for Morton {
+    fn byte_len(&self) -> usize {
+        Morton::byte_len(self)
+    }
+}
+
 impl Layout for DynLayout {
     fn byte_len(&self) -> usize {
         DynLayout::byte_len(self)
@@ -576,11 +914,14 @@ impl<P> TryMend<Matrix> for Pixel<P> {
 }
 
 /// Convert a pixel to an element, discarding the exact type information.
+///
+/// The texel is not known, use `Element::from_pixel` to retain it.
 impl<P> From<Pixel<P>> for Element {
     fn from(pix: Pixel<P>) -> Self {
         Element {
             size: pix.size(),
             align: pix.align(),
+            texel: None,
         }
     }
 }
@@ -633,6 +974,27 @@ impl cmp::PartialOrd for Element {
     }
 }
 
+/// Elements are equal if they have the same size and alignment, regardless of their texels.
+impl cmp::PartialEq for Element {
+    fn eq(&self, other: &Self) -> bool {
+        (self.size, self.align) == (other.size, other.align)
+    }
+}
+
+impl cmp::Eq for Element {}
+
+impl cmp::Ord for Element {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        (self.size, self.align).cmp(&(other.size, other.align))
+    }
+}
+
+impl hash::Hash for Element {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        (self.size, self.align).hash(state)
+    }
+}
+
 macro_rules! bytes_from_layout {
     ($layout:path) => {
         impl From<$layout> for Bytes {
@@ -672,11 +1034,7 @@ impl From<Yuv420p> for DynLayout {
 
 impl<P> From<TMatrix<P>> for Matrix {
     fn from(mat: TMatrix<P>) -> Self {
-        Matrix {
-            element: mat.pixel.into(),
-            first_dim: mat.first_dim,
-            second_dim: mat.second_dim,
-        }
+        mat.into_matrix()
     }
 }
 
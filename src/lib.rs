@@ -10,7 +10,8 @@
 //! ```
 //! # fn send_over_network(_: &[u8]) { };
 //! use canvas::Matrix;
-//! let mut canvas = Matrix::with_width_and_height(400, 400);
+//! use canvas::endian::BigEndian;
+//! let mut canvas = Matrix::<[u8; 4]>::with_width_and_height(400, 400);
 //!
 //! // Draw a bright red line.
 //! for i in 0..400 {
@@ -19,15 +20,19 @@
 //! }
 //!
 //! // Encode to network endian.
-//! let mut encoded = canvas.transmute::<u32>();
-//! encoded
-//!     .as_mut_slice()
-//!     .iter_mut()
-//!     .for_each(|p| *p = p.to_be());
+//! let encoded = canvas.transmute::<u32>().map(BigEndian::new);
 //!
 //! // Send the raw bytes
 //! send_over_network(encoded.as_bytes());
 //! ```
+//!
+//! ## Features
+//!
+//! * `image`: conversions between matrices and the `ImageBuffer` and `DynamicImage` of the
+//!   `image` crate.
+//! * `ndarray`: views of matrices and strided canvases as arrays of the `ndarray` crate, and
+//!   conversions from arrays into matrices.
+//! * `derive`: `#[derive(AsPixel)]` for plain structs of samples.
 // Be std for doctests, avoids a weird warning about missing allocator.
 #![cfg_attr(not(doctest), no_std)]
 // The only module allowed to be `unsafe` is `pixel`. We need it however, as we have a custom
@@ -35,19 +40,111 @@
 #![deny(unsafe_code)]
 extern crate alloc;
 
+pub mod alpha;
+pub mod bits;
 mod buf;
 mod canvas;
+pub mod color;
+pub mod composite;
 pub mod drm;
+pub mod endian;
+pub mod hash;
+#[cfg(feature = "image")]
+mod image;
 pub mod layout;
 mod matrix;
+pub mod metrics;
+pub mod mip;
+mod morton;
+#[cfg(feature = "ndarray")]
+mod ndarray;
+pub mod palette;
 mod pixel;
 mod rec;
+pub mod resample;
+mod rows;
+pub mod stats;
 pub mod stride;
+pub mod swizzle;
+mod texel;
+pub mod transfer;
 
-pub use self::canvas::Canvas;
+pub use self::canvas::{Canvas, Raster, RasterMut, View, ViewMut};
 pub use self::matrix::{Layout, Matrix, MatrixReuseError};
 pub use self::pixel::{AsPixel, Pixel};
 pub use self::rec::{Rec, ReuseError};
+pub use self::rows::{BandMut, BandsMut, Rows, RowsMut};
+
+/// Derive `AsPixel` for a `#[repr(C)]` struct that is `bytemuck::Pod`.
+///
+/// The optional attribute `#[texel(kind = .., channels = ..)]` names a
+/// [`SampleKind`](layout::SampleKind) and [`Channels`](layout::Channels) to describe the texel,
+/// with one channel per field. The bits of a channel default to the size of its field and can be
+/// overridden with `#[texel(bits = ..)]` on the field.
+///
+/// ```
+/// use canvas::{AsPixel, Matrix};
+/// use canvas::layout::{Channels, SampleKind, Texel};
+///
+/// #[derive(AsPixel, Clone, Copy, Default)]
+/// #[texel(kind = UNorm, channels = Rgba)]
+/// #[repr(C)]
+/// struct Rgba16 {
+///     r: u16,
+///     g: u16,
+///     b: u16,
+///     #[texel(bits = 12)]
+///     a: u16,
+/// }
+///
+/// unsafe impl bytemuck::Zeroable for Rgba16 {}
+/// unsafe impl bytemuck::Pod for Rgba16 {}
+///
+/// let texel = Texel::new(SampleKind::UNorm, Channels::Rgba, [16, 16, 16, 12]);
+/// assert_eq!(Rgba16::texel(), Some(texel));
+///
+/// let matrix = Matrix::<Rgba16>::with_width_and_height(4, 4);
+/// assert_eq!(matrix.as_bytes().len(), 128);
+/// ```
+///
+/// Structs without `#[repr(C)]` are rejected, as their field order is not fixed.
+///
+/// ```compile_fail
+/// use canvas::AsPixel;
+///
+/// #[derive(AsPixel, Clone, Copy)]
+/// struct Gray {
+///     v: u8,
+/// }
+///
+/// unsafe impl bytemuck::Zeroable for Gray {}
+/// unsafe impl bytemuck::Pod for Gray {}
+/// ```
+///
+/// A generic struct is only a pixel if all its type parameters are `Pod`.
+///
+/// ```compile_fail
+/// use canvas::{AsPixel, Matrix};
+///
+/// #[derive(AsPixel, Clone, Copy)]
+/// #[repr(C)]
+/// struct Pair<T> {
+///     a: T,
+///     b: T,
+/// }
+///
+/// unsafe impl<T: Copy + 'static> bytemuck::Zeroable for Pair<T> {}
+/// unsafe impl<T: Copy + 'static> bytemuck::Pod for Pair<T> {}
+///
+/// let matrix = Matrix::<Pair<char>>::with_width_and_height(1, 1);
+/// ```
+#[cfg(feature = "derive")]
+pub use image_canvas_derive::AsPixel;
+
+// Used by the code generated by the derive macro.
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub use bytemuck as __bytemuck;
 
 /// Constants for predefined pixel types.
 pub mod pixels {
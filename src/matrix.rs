@@ -6,6 +6,7 @@ use core::{cmp, fmt};
 
 use crate::buf::Buffer;
 use crate::canvas::{Canvas, RawCanvas};
+use crate::endian::SwapBytes;
 use crate::{layout, AsPixel, Pixel, Rec, ReuseError};
 
 /// A 2d, width-major matrix of pixels.
@@ -215,6 +216,18 @@ impl<P> Matrix<P> {
         self.inner.as_mut_slice()
     }
 
+    /// Reverse the byte order of all samples in place.
+    ///
+    /// This converts the matrix between native and a foreign byte order.
+    pub fn swap_bytes(&mut self)
+    where
+        P: SwapBytes,
+    {
+        self.as_mut_slice()
+            .iter_mut()
+            .for_each(|p| *p = p.swap_bytes())
+    }
+
     pub fn as_bytes(&self) -> &[u8] {
         self.inner.as_bytes()
     }
@@ -258,7 +271,7 @@ impl<P> Matrix<P> {
     }
 
     /// Get the layout of the matrix.
-    fn layout(&self) -> Layout<P> {
+    pub fn layout(&self) -> Layout<P> {
         *self.inner.layout()
     }
 
@@ -489,6 +502,19 @@ impl<P> layout::SampleSlice for Layout<P> {
     }
 }
 
+impl<P> layout::Take for Layout<P> {
+    fn take(&mut self) -> Self {
+        core::mem::replace(
+            self,
+            Layout {
+                width: 0,
+                height: 0,
+                pixel: self.pixel,
+            },
+        )
+    }
+}
+
 impl<P> Clone for Layout<P> {
     fn clone(&self) -> Self {
         Layout {
@@ -614,4 +640,52 @@ mod tests {
             .reuse(layout)
             .expect("Can still reuse original allocation");
     }
+
+    #[test]
+    fn array_pixels() {
+        use crate::layout::{Channels, Element, SampleKind, Texel};
+
+        let rgb = Element::from_pixel::<[u8; 3]>().texel();
+        assert_eq!(
+            rgb,
+            Some(Texel::new(SampleKind::UNorm, Channels::Rgb, [8, 8, 8, 0]))
+        );
+        let rgba = Element::from_pixel::<[f32; 4]>().texel();
+        assert_eq!(
+            rgba,
+            Some(Texel::new(SampleKind::Float, Channels::Rgba, [32; 4]))
+        );
+        let pair = Element::from_pixel::<[i16; 2]>().texel();
+        assert_eq!(
+            pair,
+            Some(Texel::new(
+                SampleKind::SInt,
+                Channels::GrayAlpha,
+                [16, 16, 0, 0]
+            ))
+        );
+        let gray_alpha = Element::from_pixel::<[u8; 2]>().texel();
+        assert_eq!(
+            gray_alpha,
+            Some(Texel::new(
+                SampleKind::UNorm,
+                Channels::GrayAlpha,
+                [8, 8, 0, 0]
+            ))
+        );
+        assert_eq!(
+            Element::from_pixel::<[u16; 1]>().texel(),
+            Element::from_pixel::<u16>().texel()
+        );
+        assert_eq!(Element::from_pixel::<[u16; 8]>().texel(), None);
+        assert_eq!(Element::from_pixel::<[[u8; 3]; 2]>().texel(), None);
+
+        let mut matrix = Matrix::<[u16; 8]>::with_width_and_height(2, 2);
+        matrix[(1, 1)] = [7; 8];
+        assert_eq!(matrix.as_bytes().len(), 64);
+        assert_eq!(
+            matrix.as_bytes()[48..].iter().filter(|&&b| b == 7).count(),
+            8
+        );
+    }
 }
@@ -0,0 +1,345 @@
+//! Differences between two images of the same dimensions.
+//!
+//! The metrics are computed per channel, on sample values in their natural range: 0 to 255 for
+//! `u8`, 0 to 65535 for `u16` and 0 to 1 for floating point samples. This range also determines
+//! the peak signal of the PSNR and the stabilizing constants of the SSIM.
+//!
+//! The SSIM is the mean of the structural similarity over 8×8 windows, placed every four pixels.
+//! Images smaller than a window in one direction use windows that span that whole direction.
+//!
+//! ```
+//! use canvas::Matrix;
+//! use canvas::metrics::Comparison;
+//!
+//! let reference = Matrix::<[u8; 3]>::with_width_and_height(16, 16);
+//! let mut decoded = reference.clone();
+//! decoded[(3, 4)] = [0, 2, 0];
+//!
+//! let comparison = Comparison::new(&reference, &decoded);
+//! assert_eq!(comparison.channels()[1].max_abs_diff, 2.0);
+//! assert_eq!(comparison.max_abs_diff(), 2.0);
+//! assert!(comparison.psnr() > 50.0);
+//! assert!(comparison.ssim() > 0.99);
+//! ```
+use alloc::vec::Vec;
+
+use crate::pixel::AsPixel;
+use crate::stride::ByteCanvasRef;
+use crate::Matrix;
+
+/// A numeric sample of a channel.
+pub trait Sample: Copy {
+    /// The largest value of a sample with full intensity.
+    const PEAK: f64;
+
+    /// Convert into a floating point value.
+    fn to_f64(self) -> f64;
+}
+
+/// A pixel consisting of channels with one sample each.
+///
+/// This is implemented for all samples, as a single channel, and for arrays of samples.
+pub trait Samples {
+    /// The type of each channel.
+    type Sample: Sample;
+    /// The number of channels.
+    const CHANNELS: usize;
+
+    /// The samples of all channels, in order.
+    fn samples(&self) -> &[Self::Sample];
+}
+
+/// The differences between the samples of one channel.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChannelMetrics {
+    /// The largest absolute difference of any sample.
+    pub max_abs_diff: f64,
+    /// The mean squared error.
+    pub mse: f64,
+    /// The peak signal-to-noise ratio in decibel, infinite for equal channels.
+    pub psnr: f64,
+    /// The mean structural similarity, 1 for equal channels.
+    pub ssim: f64,
+}
+
+/// The differences between two images, per channel.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Comparison {
+    channels: Vec<ChannelMetrics>,
+    peak: f64,
+}
+
+macro_rules! sample_types {
+    ($($type:ty => $peak:expr),*) => {
+        $(
+            impl Sample for $type {
+                const PEAK: f64 = $peak;
+
+                fn to_f64(self) -> f64 {
+                    self.into()
+                }
+            }
+
+            impl Samples for $type {
+                type Sample = $type;
+                const CHANNELS: usize = 1;
+
+                fn samples(&self) -> &[$type] {
+                    core::slice::from_ref(self)
+                }
+            }
+        )*
+    };
+}
+
+sample_types!(u8 => 255.0, u16 => 65535.0, f32 => 1.0, f64 => 1.0);
+
+impl<T: Sample, const N: usize> Samples for [T; N] {
+    type Sample = T;
+    const CHANNELS: usize = N;
+
+    fn samples(&self) -> &[T] {
+        self
+    }
+}
+
+impl Comparison {
+    const WINDOW: usize = 8;
+    const STEP: usize = 4;
+
+    /// Compare two matrices channel by channel.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the dimensions of the matrices differ.
+    pub fn new<P: Samples>(lhs: &Matrix<P>, rhs: &Matrix<P>) -> Self {
+        let (lhs_layout, rhs_layout) = (lhs.layout(), rhs.layout());
+        assert!(
+            (lhs_layout.width(), lhs_layout.height()) == (rhs_layout.width(), rhs_layout.height()),
+            "Mismatching dimensions."
+        );
+
+        let (width, height) = (lhs_layout.width(), lhs_layout.height());
+        let channels = (0..P::CHANNELS)
+            .map(|channel| {
+                let plane = |matrix: &Matrix<P>| -> Vec<f64> {
+                    matrix
+                        .as_slice()
+                        .iter()
+                        .map(|pixel| pixel.samples()[channel].to_f64())
+                        .collect()
+                };
+                let peak = <P::Sample as Sample>::PEAK;
+                Self::channel(&plane(lhs), &plane(rhs), width, height, peak)
+            })
+            .collect();
+
+        Comparison {
+            channels,
+            peak: <P::Sample as Sample>::PEAK,
+        }
+    }
+
+    /// Compare two strided canvases, such as the planes of DRM frame buffers.
+    ///
+    /// The elements of both canvases are interpreted as pixels of type `P`, regardless of their
+    /// strides. Returns `None` if the dimensions of the canvases differ or if their element size
+    /// is not the size of `P`.
+    pub fn with_canvases<P: Samples + AsPixel>(
+        lhs: ByteCanvasRef<'_>,
+        rhs: ByteCanvasRef<'_>,
+    ) -> Option<Self> {
+        let (lhs_spec, rhs_spec) = (lhs.layout().spec(), rhs.layout().spec());
+        let size = P::pixel().size();
+        if (lhs_spec.width, lhs_spec.height) != (rhs_spec.width, rhs_spec.height)
+            || lhs_spec.element.size() != size
+            || rhs_spec.element.size() != size
+        {
+            return None;
+        }
+
+        Some(Comparison::new(
+            &lhs.to_matrix::<P>(),
+            &rhs.to_matrix::<P>(),
+        ))
+    }
+
+    /// The metrics of each channel.
+    pub fn channels(&self) -> &[ChannelMetrics] {
+        &self.channels
+    }
+
+    /// The largest absolute difference in any channel.
+    pub fn max_abs_diff(&self) -> f64 {
+        self.channels
+            .iter()
+            .map(|channel| channel.max_abs_diff)
+            .fold(0.0, f64::max)
+    }
+
+    /// The mean squared error over all channels.
+    pub fn mse(&self) -> f64 {
+        self.mean(|channel| channel.mse, 0.0)
+    }
+
+    /// The peak signal-to-noise ratio over all channels, in decibel.
+    pub fn psnr(&self) -> f64 {
+        Self::psnr_of(self.mse(), self.peak)
+    }
+
+    /// The mean structural similarity over all channels.
+    pub fn ssim(&self) -> f64 {
+        self.mean(|channel| channel.ssim, 1.0)
+    }
+
+    fn mean(&self, metric: impl Fn(&ChannelMetrics) -> f64, empty: f64) -> f64 {
+        if self.channels.is_empty() {
+            return empty;
+        }
+
+        self.channels.iter().map(metric).sum::<f64>() / self.channels.len() as f64
+    }
+
+    fn psnr_of(mse: f64, peak: f64) -> f64 {
+        if mse == 0.0 {
+            f64::INFINITY
+        } else {
+            10.0 * libm::log10(peak * peak / mse)
+        }
+    }
+
+    fn channel(lhs: &[f64], rhs: &[f64], width: usize, height: usize, peak: f64) -> ChannelMetrics {
+        let (mut max_abs_diff, mut sum_sq) = (0.0f64, 0.0);
+        for (&a, &b) in lhs.iter().zip(rhs) {
+            let diff = a - b;
+            max_abs_diff = max_abs_diff.max(diff.abs());
+            sum_sq += diff * diff;
+        }
+
+        let mse = if lhs.is_empty() {
+            0.0
+        } else {
+            sum_sq / lhs.len() as f64
+        };
+
+        ChannelMetrics {
+            max_abs_diff,
+            mse,
+            psnr: Self::psnr_of(mse, peak),
+            ssim: Self::windowed_ssim(lhs, rhs, width, height, peak),
+        }
+    }
+
+    fn windowed_ssim(lhs: &[f64], rhs: &[f64], width: usize, height: usize, peak: f64) -> f64 {
+        if width == 0 || height == 0 {
+            return 1.0;
+        }
+
+        let c1 = (0.01 * peak) * (0.01 * peak);
+        let c2 = (0.03 * peak) * (0.03 * peak);
+        let (win_w, win_h) = (width.min(Self::WINDOW), height.min(Self::WINDOW));
+        let count = (win_w * win_h) as f64;
+
+        let (mut total, mut windows) = (0.0, 0usize);
+        for y in (0..=height - win_h).step_by(Self::STEP) {
+            for x in (0..=width - win_w).step_by(Self::STEP) {
+                let (mut sa, mut sb, mut saa, mut sbb, mut sab) = (0.0, 0.0, 0.0, 0.0, 0.0);
+                for row in y..y + win_h {
+                    let start = row * width + x;
+                    for (&a, &b) in lhs[start..start + win_w]
+                        .iter()
+                        .zip(&rhs[start..start + win_w])
+                    {
+                        sa += a;
+                        sb += b;
+                        saa += a * a;
+                        sbb += b * b;
+                        sab += a * b;
+                    }
+                }
+
+                let (mean_a, mean_b) = (sa / count, sb / count);
+                let var_a = saa / count - mean_a * mean_a;
+                let var_b = sbb / count - mean_b * mean_b;
+                let cov = sab / count - mean_a * mean_b;
+
+                total += ((2.0 * mean_a * mean_b + c1) * (2.0 * cov + c2))
+                    / ((mean_a * mean_a + mean_b * mean_b + c1) * (var_a + var_b + c2));
+                windows += 1;
+            }
+        }
+
+        total / windows as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drm::{DrmFramebufferCmd, DrmLayout, FourCC, PlaneIdx};
+    use crate::stride::Strided;
+
+    #[test]
+    fn equal_images() {
+        let matrix = Matrix::<[u16; 4]>::with_width_and_height(5, 3);
+        let comparison = Comparison::new(&matrix, &matrix);
+        assert_eq!(comparison.channels().len(), 4);
+        assert_eq!(comparison.max_abs_diff(), 0.0);
+        assert_eq!(comparison.mse(), 0.0);
+        assert_eq!(comparison.psnr(), f64::INFINITY);
+        assert!((comparison.ssim() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn differences() {
+        let mut lhs = Matrix::<u8>::with_width_and_height(16, 16);
+        for (idx, p) in lhs.as_mut_slice().iter_mut().enumerate() {
+            *p = (idx * 7 % 256) as u8;
+        }
+
+        let mut rhs = lhs.clone();
+        rhs.as_mut_slice().iter_mut().for_each(|p| *p ^= 1);
+        let comparison = Comparison::new(&lhs, &rhs);
+        assert_eq!(comparison.max_abs_diff(), 1.0);
+        assert_eq!(comparison.mse(), 1.0);
+        assert!((comparison.psnr() - 48.1308).abs() < 1e-3);
+        assert!(comparison.ssim() > 0.99);
+
+        // Inverting the image destroys its structure.
+        let mut inverted = lhs.clone();
+        inverted.as_mut_slice().iter_mut().for_each(|p| *p = !*p);
+        assert!(Comparison::new(&lhs, &inverted).ssim() < 0.0);
+
+        let mut float = Matrix::<f32>::with_width_and_height(2, 2);
+        float[(1, 1)] = 0.5;
+        let comparison = Comparison::new(&float, &Matrix::with_width_and_height(2, 2));
+        assert_eq!(comparison.mse(), 0.0625);
+        assert!((comparison.psnr() - 12.0412).abs() < 1e-3);
+    }
+
+    #[test]
+    fn strided_planes() {
+        let cmd = |pitch: u32| DrmFramebufferCmd {
+            width: 4,
+            height: 2,
+            fourcc: FourCC::C8,
+            flags: 0,
+            pitches: [pitch, 0, 0, 0],
+            offsets: [0; 4],
+            modifier: [0; 4],
+        };
+
+        let padded = DrmLayout::new(&cmd(8)).unwrap().plane(PlaneIdx::First);
+        let packed = DrmLayout::new(&cmd(4)).unwrap().plane(PlaneIdx::First);
+        let padded_bytes = *b"abcd....efgh....";
+        let packed_bytes = *b"abcdefgh";
+        let lhs = ByteCanvasRef::with_bytes(padded.unwrap().strided(), &padded_bytes).unwrap();
+        let rhs = ByteCanvasRef::with_bytes(packed.unwrap().strided(), &packed_bytes).unwrap();
+        assert!(lhs == rhs);
+
+        let comparison = Comparison::with_canvases::<u8>(lhs.as_ref(), rhs.as_ref()).unwrap();
+        assert_eq!(comparison.max_abs_diff(), 0.0);
+        assert!(Comparison::with_canvases::<u16>(lhs.as_ref(), rhs.as_ref()).is_none());
+        assert!(lhs != rhs.as_ref().transposed());
+    }
+}
@@ -0,0 +1,301 @@
+//! Mipmap chains and image pyramids stored in a single buffer.
+//!
+//! Each level halves the dimensions of the previous one, rounding down but never below one pixel.
+//! The levels are stored one after another as packed, row-major matrices, starting with the full
+//! resolution image at level zero. The same layout holds Gaussian pyramids, which usually stop
+//! before reaching a single pixel.
+//!
+//! ```
+//! use canvas::Matrix;
+//! use canvas::mip::Mipmapper;
+//! use canvas::resample::Filter;
+//! use canvas::transfer::TransferFunction;
+//!
+//! let mut base = Matrix::<[u8; 4]>::with_width_and_height(4, 2);
+//! base.as_mut_slice()[..4].copy_from_slice(&[[0xff; 4]; 4]);
+//!
+//! let chain = Mipmapper::new(Filter::Box)
+//!     .with_transfer(TransferFunction::Srgb)
+//!     .chain(&base);
+//! assert_eq!(chain.layout().levels(), 3);
+//!
+//! // Half of the light of a white pixel, encoded as sRGB. Alpha is averaged as is.
+//! let last = chain.layout().level_offset(2).unwrap();
+//! assert_eq!(chain.as_bytes()[last..], [0xbc, 0xbc, 0xbc, 0x80]);
+//! ```
+use core::convert::TryFrom;
+
+use crate::layout::{Element, Layout, Matrix as MatrixLayout};
+use crate::pixel::AsPixel;
+use crate::resample::{Filter, Linearize, Resample, Resampler};
+use crate::stride::StrideLayout;
+use crate::transfer::TransferFunction;
+use crate::{Canvas, Matrix};
+
+/// The layout of the levels of a mipmap chain or pyramid in one buffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MipChain {
+    element: Element,
+    width: usize,
+    height: usize,
+    levels: usize,
+}
+
+/// Generates the smaller levels of a mipmap chain from its first level.
+#[derive(Clone, Copy, Debug)]
+pub struct Mipmapper {
+    filter: Filter,
+    transfer: Option<TransferFunction>,
+}
+
+impl MipChain {
+    /// Create the layout of a full chain, down to a single pixel.
+    ///
+    /// Returns `None` if a dimension is zero or the chain does not fit into memory.
+    pub fn new(element: Element, width: usize, height: usize) -> Option<Self> {
+        Self::with_levels(element, width, height, usize::MAX)
+    }
+
+    /// Create the layout of a chain with at most the given number of levels.
+    ///
+    /// Returns `None` if a dimension or the number of levels is zero, or if the chain does not
+    /// fit into memory.
+    pub fn with_levels(
+        element: Element,
+        width: usize,
+        height: usize,
+        levels: usize,
+    ) -> Option<Self> {
+        if width == 0 || height == 0 || levels == 0 {
+            return None;
+        }
+
+        let full = (usize::BITS - width.max(height).leading_zeros()) as usize;
+        let chain = MipChain {
+            element,
+            width,
+            height,
+            levels: levels.min(full),
+        };
+
+        // Validates the total size, all offsets are smaller.
+        let byte_len = chain.offset_of(chain.levels)?;
+        isize::try_from(byte_len).ok()?;
+        Some(chain)
+    }
+
+    /// Get the element type of this layout.
+    pub fn element(&self) -> Element {
+        self.element
+    }
+
+    /// Get the width of the first level.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Get the height of the first level.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The number of levels.
+    pub fn levels(&self) -> usize {
+        self.levels
+    }
+
+    /// The width and height of a level.
+    ///
+    /// Returns `None` if the level does not exist.
+    pub fn level_dimensions(&self, level: usize) -> Option<(usize, usize)> {
+        if level >= self.levels {
+            return None;
+        }
+
+        Some(Self::dimensions_at(self.width, self.height, level))
+    }
+
+    /// The byte offset of the first element of a level.
+    ///
+    /// Returns `None` if the level does not exist.
+    pub fn level_offset(&self, level: usize) -> Option<usize> {
+        if level >= self.levels {
+            return None;
+        }
+
+        // Does not overflow due to construction.
+        Some(self.offset_of(level).unwrap())
+    }
+
+    /// Get the layout of a level within the buffer of the chain.
+    ///
+    /// Returns `None` if the level does not exist.
+    pub fn level_layout(&self, level: usize) -> Option<StrideLayout> {
+        let (width, height) = self.level_dimensions(level)?;
+        let matrix = MatrixLayout::from_width_height(self.element, width, height)
+            .expect("Fits in memory because the chain does");
+        let spec = crate::stride::StrideSpec {
+            offset: self.level_offset(level)?,
+            ..StrideLayout::with_row_major(matrix).spec()
+        };
+
+        let layout = StrideLayout::new(spec);
+        Some(layout.expect("Fits in memory and is aligned because the chain is valid"))
+    }
+
+    /// Get the required bytes for this layout.
+    pub fn byte_len(&self) -> usize {
+        // Does not overflow due to construction.
+        self.offset_of(self.levels).unwrap()
+    }
+
+    fn dimensions_at(width: usize, height: usize, level: usize) -> (usize, usize) {
+        ((width >> level).max(1), (height >> level).max(1))
+    }
+
+    /// The sum of the sizes of all levels before the given one.
+    fn offset_of(&self, level: usize) -> Option<usize> {
+        (0..level).try_fold(0usize, |offset, level| {
+            let (width, height) = Self::dimensions_at(self.width, self.height, level);
+            let len = width
+                .checked_mul(height)?
+                .checked_mul(self.element.size())?;
+            offset.checked_add(len)
+        })
+    }
+}
+
+impl Mipmapper {
+    /// Generate levels with a filter, averaging the encoded sample values.
+    pub fn new(filter: Filter) -> Self {
+        Mipmapper {
+            filter,
+            transfer: None,
+        }
+    }
+
+    /// Filter the color channels in linear light, by decoding them with a transfer function.
+    ///
+    /// This is the gamma-correct way to average colors. The last channel of pixels with two or
+    /// four channels is alpha and is always filtered as is.
+    pub fn with_transfer(self, function: TransferFunction) -> Self {
+        Mipmapper {
+            transfer: Some(function),
+            ..self
+        }
+    }
+
+    /// Allocate a full chain for an image and generate all of its levels.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the image is empty or the chain does not fit into memory.
+    pub fn chain<P: Resample + AsPixel>(&self, base: &Matrix<P>) -> Canvas<MipChain> {
+        let layout = base.layout();
+        let chain = MipChain::new(P::pixel().into(), layout.width(), layout.height())
+            .expect("Mipmap chain of an empty image");
+
+        let mut canvas = Canvas::new(chain);
+        canvas.as_bytes_mut()[..base.as_bytes().len()].copy_from_slice(base.as_bytes());
+        self.generate::<P>(&mut canvas);
+        canvas
+    }
+
+    /// Generate all levels of a chain from its first level.
+    ///
+    /// Each level is filtered from the previous one.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the element size of the chain is not the size of `P`.
+    pub fn generate<P: Resample + AsPixel>(&self, canvas: &mut Canvas<MipChain>) {
+        let chain = *canvas.layout();
+        let size = P::pixel().size();
+        assert!(chain.element.size() == size, "Mismatching pixel size.");
+
+        let transfer = self.transfer.map(Linearize::<P::Sample>::new);
+        let pixels = canvas.as_mut_pixels(P::pixel());
+        for level in 1..chain.levels {
+            let from = chain.level_dimensions(level - 1).unwrap();
+            let to = chain.level_dimensions(level).unwrap();
+            let start = chain.level_offset(level).unwrap() / size;
+            let (previous, rest) = pixels.split_at_mut(start);
+            let source = &previous[chain.level_offset(level - 1).unwrap() / size..];
+
+            Resampler::new(self.filter, from, to).resample_slices(
+                source,
+                &mut rest[..to.0 * to.1],
+                transfer.as_ref(),
+            );
+        }
+    }
+}
+
+impl Layout for MipChain {
+    fn byte_len(&self) -> usize {
+        MipChain::byte_len(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pixels::{F32, U16};
+    use crate::stride::ByteCanvasRef;
+
+    #[test]
+    fn chain_layout() {
+        let chain = MipChain::new(U16.into(), 5, 3).unwrap();
+        assert_eq!(chain.levels(), 3);
+        assert_eq!(chain.level_dimensions(1), Some((2, 1)));
+        assert_eq!(chain.level_dimensions(2), Some((1, 1)));
+        assert_eq!(chain.level_offset(1), Some(30));
+        assert_eq!(chain.level_offset(2), Some(34));
+        assert_eq!(chain.byte_len(), 36);
+        assert_eq!(chain.level_offset(3), None);
+
+        let layout = chain.level_layout(1).unwrap();
+        assert_eq!((layout.spec().width, layout.spec().height), (2, 1));
+        assert_eq!(layout.byte_len(), 34);
+
+        let pyramid = MipChain::with_levels(F32.into(), 64, 64, 2).unwrap();
+        assert_eq!(pyramid.levels(), 2);
+        assert_eq!(pyramid.byte_len(), (64 * 64 + 32 * 32) * 4);
+
+        assert!(MipChain::new(U16.into(), 0, 4).is_none());
+        assert!(MipChain::new(U16.into(), usize::MAX, 2).is_none());
+    }
+
+    #[test]
+    fn generate_levels() {
+        let mut base = Matrix::<u16>::with_width_and_height(4, 4);
+        for (idx, p) in base.as_mut_slice().iter_mut().enumerate() {
+            *p = 100 * (idx as u16 % 4);
+        }
+
+        let chain = Mipmapper::new(Filter::Box).chain(&base);
+        let level = |idx: usize| {
+            let layout = chain.layout().level_layout(idx).unwrap();
+            ByteCanvasRef::with_bytes(layout, chain.as_bytes())
+                .unwrap()
+                .to_matrix::<u16>()
+        };
+
+        assert_eq!(level(0), base);
+        assert_eq!(level(1).as_slice(), &[50, 250, 50, 250]);
+        assert_eq!(level(2).as_slice(), &[150]);
+
+        // A constant image stays constant, also with the negative lobes of the Kaiser filter.
+        let mut flat = Matrix::<[f32; 2]>::with_width_and_height(6, 5);
+        flat.as_mut_slice()
+            .iter_mut()
+            .for_each(|p| *p = [0.25, 1.0]);
+        let chain = Mipmapper::new(Filter::Kaiser)
+            .with_transfer(TransferFunction::Srgb)
+            .chain(&flat);
+        let pixels = chain.as_pixels(F32.array2());
+        assert!(pixels[..chain.layout().byte_len() / 8]
+            .iter()
+            .all(|&[c, a]| (c - 0.25).abs() < 1e-4 && (a - 1.0).abs() < 1e-6));
+    }
+}
@@ -0,0 +1,162 @@
+//! Conversions between row-major matrices and the Morton order layout.
+use core::convert::TryFrom;
+
+use crate::canvas::{Raster, RasterMut, View, ViewMut};
+use crate::layout::{Coord, Element, Morton};
+use crate::{AsPixel, Canvas, Matrix};
+
+/// Conversions into and from the Morton order.
+impl<P: AsPixel + Copy> Matrix<P> {
+    /// Copy the pixels into a canvas in Morton order.
+    ///
+    /// The padding of the canvas is zeroed. Returns `None` if a dimension does not fit into a
+    /// `u32` or the padded layout does not fit into memory.
+    ///
+    /// ```
+    /// use canvas::Matrix;
+    /// use canvas::layout::Coord;
+    ///
+    /// let mut matrix = Matrix::<u16>::with_width_and_height(3, 2);
+    /// matrix[(2, 1)] = 42;
+    ///
+    /// let morton = matrix.to_morton().unwrap();
+    /// assert_eq!(morton.get::<u16>(Coord(2, 1)), Some(42));
+    /// assert_eq!(Matrix::from_morton(&morton), Some(matrix));
+    /// ```
+    pub fn to_morton(&self) -> Option<Canvas<Morton>> {
+        let layout = self.layout();
+        let width = u32::try_from(layout.width()).ok()?;
+        let height = u32::try_from(layout.height()).ok()?;
+        let morton = Morton::new(Element::from(P::pixel()), width, height)?;
+
+        let mut canvas = Canvas::new(morton);
+        let pixels = canvas.as_mut_pixels(P::pixel());
+        for (y, row) in self.rows().enumerate() {
+            for (x, &pixel) in row.iter().enumerate() {
+                // Coordinates fit into `u32` by the checks above.
+                let index = morton.index(Coord(x as u32, y as u32)).unwrap();
+                pixels[index] = pixel;
+            }
+        }
+
+        Some(canvas)
+    }
+
+    /// Copy the pixels of a canvas in Morton order into a new matrix.
+    ///
+    /// Returns `None` if the element size of the layout is not the size of `P`.
+    pub fn from_morton(canvas: &Canvas<Morton>) -> Option<Self> {
+        let morton = *canvas.layout();
+        if morton.element().size() != P::pixel().size() {
+            return None;
+        }
+
+        let mut matrix =
+            Matrix::with_width_and_height(morton.width() as usize, morton.height() as usize);
+        let pixels = canvas.as_pixels(P::pixel());
+        for (y, row) in matrix.rows_mut().enumerate() {
+            for (x, pixel) in row.iter_mut().enumerate() {
+                let index = morton.index(Coord(x as u32, y as u32)).unwrap();
+                *pixel = pixels[index];
+            }
+        }
+
+        Some(matrix)
+    }
+}
+
+impl<P: AsPixel + Copy> Raster<P> for Morton {
+    fn dimensions(&self) -> Coord {
+        Coord(self.width(), self.height())
+    }
+
+    /// Read a pixel.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the element size of the layout is not the size of `P`.
+    fn get(from: View<Self>, at: Coord) -> P {
+        let index = pixel_index::<P>(from.layout(), at);
+        from.as_pixels(P::pixel())[index]
+    }
+}
+
+impl<P: AsPixel + Copy> RasterMut<P> for Morton {
+    /// Write a pixel.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the element size of the layout is not the size of `P`.
+    fn put(mut into: ViewMut<Self>, at: Coord, val: P) {
+        let index = pixel_index::<P>(into.layout(), at);
+        into.as_mut_pixels(P::pixel())[index] = val;
+    }
+}
+
+fn pixel_index<P: AsPixel>(morton: &Morton, at: Coord) -> usize {
+    assert!(
+        morton.element().size() == P::pixel().size(),
+        "Mismatching pixel size."
+    );
+    morton.index(at).expect("Coordinate out of bounds.")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn morton_order() {
+        let square = Morton::new(Element::from(u8::pixel()), 4, 4).unwrap();
+        let order: alloc::vec::Vec<_> = (0..16)
+            .map(|i| square.index(Coord(i % 4, i / 4)).unwrap())
+            .collect();
+        assert_eq!(
+            order,
+            [0, 1, 4, 5, 2, 3, 6, 7, 8, 9, 12, 13, 10, 11, 14, 15]
+        );
+
+        // Wide images store square blocks one after another.
+        let wide = Morton::new(Element::from(u8::pixel()), 5, 2).unwrap();
+        assert_eq!((wide.padded_width(), wide.padded_height()), (8, 2));
+        assert_eq!(wide.byte_len(), 16);
+        assert_eq!(wide.index(Coord(1, 1)), Some(3));
+        assert_eq!(wide.index(Coord(2, 0)), Some(4));
+        assert_eq!(wide.index(Coord(4, 1)), Some(10));
+        assert_eq!(wide.index(Coord(5, 0)), None);
+
+        let tall = Morton::new(Element::from(u16::pixel()), 1, 3).unwrap();
+        assert_eq!(tall.offset(Coord(0, 2)), Some(4));
+
+        let empty = Morton::new(Element::from(u8::pixel()), 0, 7).unwrap();
+        assert_eq!(empty.byte_len(), 0);
+        assert!(Morton::new(Element::from(u8::pixel()), u32::MAX, u32::MAX).is_none());
+    }
+
+    #[test]
+    fn raster_access() {
+        let mut matrix = Matrix::<[u8; 4]>::with_width_and_height(5, 3);
+        for (idx, pixel) in matrix.as_mut_slice().iter_mut().enumerate() {
+            *pixel = [idx as u8; 4];
+        }
+
+        let mut morton = matrix.to_morton().unwrap();
+        assert_eq!(morton.get::<[u8; 4]>(Coord(4, 2)), Some([14; 4]));
+        assert_eq!(morton.get::<[u8; 4]>(Coord(5, 0)), None);
+
+        morton.put(Coord(1, 1), [0xff_u8; 4]);
+        let back = Matrix::<[u8; 4]>::from_morton(&morton).unwrap();
+        assert_eq!(back[(1, 1)], [0xff; 4]);
+        assert_eq!(back[(0, 1)], [5; 4]);
+        assert!(Matrix::<u16>::from_morton(&morton).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Mismatching pixel size.")]
+    fn mismatching_pixel() {
+        let morton = Matrix::<u8>::with_width_and_height(2, 2)
+            .to_morton()
+            .unwrap();
+        morton.get::<u16>(Coord(0, 0));
+    }
+}
@@ -0,0 +1,315 @@
+//! Views and conversions with the arrays of the `ndarray` crate.
+//!
+//! The views borrow the samples of a matrix or strided canvas without copying. Matrices of sample
+//! arrays are viewed with three axes, height × width × channels, and all other matrices with two
+//! axes, height × width. Strided canvases map their strides in bytes to strides in elements,
+//! including negative ones.
+//!
+//! ```
+//! use canvas::Matrix;
+//!
+//! let mut matrix = Matrix::<[u8; 4]>::with_width_and_height(3, 2);
+//! matrix[(2, 1)] = [0, 0, 0xff, 0];
+//!
+//! let view = matrix.array_view3();
+//! assert_eq!(view.shape(), &[2, 3, 4]);
+//! assert_eq!(view[[1, 2, 2]], 0xff);
+//! ```
+use core::convert::TryFrom;
+
+use ::ndarray::{
+    ArrayBase, ArrayView2, ArrayView3, ArrayViewMut2, ArrayViewMut3, Axis, Data, Ix2, Ix3,
+    ShapeBuilder,
+};
+
+use crate::layout::MismatchedPixelError;
+use crate::stride::{ByteCanvasMut, ByteCanvasRef, StrideSpec};
+use crate::{AsPixel, Layout, Matrix, Pixel};
+
+impl<P> Matrix<P> {
+    /// View the pixels with two axes, height × width.
+    pub fn array_view(&self) -> ArrayView2<'_, P> {
+        let layout = self.layout();
+        ArrayView2::from_shape((layout.height(), layout.width()), self.as_slice())
+            .expect("Matrix is a standard layout")
+    }
+
+    /// View the pixels mutably with two axes, height × width.
+    pub fn array_view_mut(&mut self) -> ArrayViewMut2<'_, P> {
+        let layout = self.layout();
+        ArrayViewMut2::from_shape((layout.height(), layout.width()), self.as_mut_slice())
+            .expect("Matrix is a standard layout")
+    }
+}
+
+impl<T, const N: usize> Matrix<[T; N]> {
+    /// View the samples with three axes, height × width × channels.
+    pub fn array_view3(&self) -> ArrayView3<'_, T> {
+        let layout = self.layout();
+        let shape = (layout.height(), layout.width(), N);
+        ArrayView3::from_shape(shape, self.as_slice().as_flattened())
+            .expect("Matrix is a standard layout")
+    }
+
+    /// View the samples mutably with three axes, height × width × channels.
+    pub fn array_view3_mut(&mut self) -> ArrayViewMut3<'_, T> {
+        let layout = self.layout();
+        let shape = (layout.height(), layout.width(), N);
+        ArrayViewMut3::from_shape(shape, self.as_mut_slice().as_flattened_mut())
+            .expect("Matrix is a standard layout")
+    }
+}
+
+impl<'data> ByteCanvasRef<'data> {
+    /// View the elements as a typed array with two axes, height × width.
+    ///
+    /// Returns `None` if the element does not have the size of `T`, if a stride is not a multiple
+    /// of it or if the data is not aligned for `T`.
+    pub fn array_view<T: bytemuck::Pod>(&self) -> Option<ArrayView2<'data, T>> {
+        let spec = self.layout().spec();
+        let (bytes, shape) = typed_region::<T>(&spec)?;
+        let samples = bytemuck::try_cast_slice(&self.data()[bytes]).ok()?;
+        let mut view = ArrayView2::from_shape(shape.strides, samples).ok()?;
+        shape.invert.invert(|axis| view.invert_axis(axis));
+        Some(view)
+    }
+}
+
+impl ByteCanvasMut<'_> {
+    /// View the elements mutably as a typed array with two axes, height × width.
+    ///
+    /// Returns `None` in the same cases as [`ByteCanvasRef::array_view`] and if elements
+    /// overlap in memory.
+    pub fn array_view_mut<T: bytemuck::Pod>(&mut self) -> Option<ArrayViewMut2<'_, T>> {
+        let spec = self.layout().spec();
+        let (bytes, shape) = typed_region::<T>(&spec)?;
+        let samples = bytemuck::try_cast_slice_mut(&mut self.data_mut()[bytes]).ok()?;
+        let mut view = ArrayViewMut2::from_shape(shape.strides, samples).ok()?;
+        shape.invert.invert(|axis| view.invert_axis(axis));
+        Some(view)
+    }
+}
+
+/// The shape of a view into the lowest addressed element, with the axes to invert afterwards.
+struct TypedShape {
+    strides: ::ndarray::StrideShape<Ix2>,
+    invert: Inverted,
+}
+
+struct Inverted {
+    height: bool,
+    width: bool,
+}
+
+impl Inverted {
+    fn invert(&self, mut with: impl FnMut(Axis)) {
+        if self.height {
+            with(Axis(0));
+        }
+        if self.width {
+            with(Axis(1));
+        }
+    }
+}
+
+/// Find the bytes covered by a strided layout and the element strides within them.
+fn typed_region<T>(spec: &StrideSpec) -> Option<(core::ops::Range<usize>, TypedShape)> {
+    let size = core::mem::size_of::<T>();
+    if spec.element.size() != size || size == 0 {
+        return None;
+    }
+
+    let isize_of = size as isize;
+    if spec.width_stride % isize_of != 0 || spec.height_stride % isize_of != 0 {
+        return None;
+    }
+
+    let invert = Inverted {
+        height: spec.height_stride < 0,
+        width: spec.width_stride < 0,
+    };
+
+    let dim = (spec.height, spec.width);
+    let strides = (
+        spec.height_stride.unsigned_abs() / size,
+        spec.width_stride.unsigned_abs() / size,
+    );
+
+    if spec.width == 0 || spec.height == 0 {
+        let shape = TypedShape {
+            strides: dim.strides(strides),
+            invert,
+        };
+        return Some((0..0, shape));
+    }
+
+    // The distances to the elements with the lowest and highest address from the first one.
+    let extent = |stride: isize, len: usize| stride * (len as isize - 1);
+    let (h, w) = (
+        extent(spec.height_stride, spec.height),
+        extent(spec.width_stride, spec.width),
+    );
+    let low = spec.offset as isize + h.min(0) + w.min(0);
+    let high = spec.offset as isize + h.max(0) + w.max(0) + isize_of;
+
+    let shape = TypedShape {
+        strides: dim.strides(strides),
+        invert,
+    };
+    Some((
+        usize::try_from(low).ok()?..usize::try_from(high).ok()?,
+        shape,
+    ))
+}
+
+impl<S, T> TryFrom<ArrayBase<S, Ix2>> for Matrix<T>
+where
+    S: Data<Elem = T>,
+    T: AsPixel + Copy,
+{
+    type Error = MismatchedPixelError;
+
+    /// Copy an array with axes height × width into a matrix.
+    ///
+    /// Fails if the matrix would not fit into memory.
+    fn try_from(array: ArrayBase<S, Ix2>) -> Result<Self, Self::Error> {
+        from_array(T::pixel(), array.view())
+    }
+}
+
+macro_rules! array3_into_matrix {
+    ($($n:literal => $array:ident),*) => {
+        $(
+            impl<S, T> TryFrom<ArrayBase<S, Ix3>> for Matrix<[T; $n]>
+            where
+                S: Data<Elem = T>,
+                T: AsPixel + Copy,
+            {
+                type Error = MismatchedPixelError;
+
+                /// Copy an array with axes height × width × channels into a matrix.
+                ///
+                /// Fails if the number of channels differs from the length of the pixel arrays or
+                /// if the matrix would not fit into memory.
+                fn try_from(array: ArrayBase<S, Ix3>) -> Result<Self, Self::Error> {
+                    let (height, width, channels) = array.dim();
+                    if channels != $n {
+                        return Err(MismatchedPixelError::default());
+                    }
+
+                    let layout =
+                        Layout::width_and_height_for_pixel(T::pixel().$array(), width, height)
+                            .ok_or_else(MismatchedPixelError::default)?;
+                    let mut matrix = Matrix::with_layout(layout);
+                    let samples = matrix.as_mut_slice().as_flattened_mut();
+                    match array.as_slice() {
+                        Some(standard) => samples.copy_from_slice(standard),
+                        None => samples
+                            .iter_mut()
+                            .zip(array.iter())
+                            .for_each(|(s, &a)| *s = a),
+                    }
+                    Ok(matrix)
+                }
+            }
+        )*
+    };
+}
+
+array3_into_matrix!(1 => array1, 2 => array2, 3 => array3, 4 => array4);
+
+fn from_array<T: Copy>(
+    pixel: Pixel<T>,
+    array: ArrayView2<'_, T>,
+) -> Result<Matrix<T>, MismatchedPixelError> {
+    let (height, width) = array.dim();
+    let layout = Layout::width_and_height_for_pixel(pixel, width, height)
+        .ok_or_else(MismatchedPixelError::default)?;
+    let mut matrix = Matrix::with_layout(layout);
+    match array.as_slice() {
+        Some(standard) => matrix.as_mut_slice().copy_from_slice(standard),
+        None => matrix
+            .as_mut_slice()
+            .iter_mut()
+            .zip(array.iter())
+            .for_each(|(s, &a)| *s = a),
+    }
+    Ok(matrix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stride::StrideLayout;
+    use ::ndarray::{s, Array2, Array3};
+
+    #[test]
+    fn matrix_views() {
+        let mut matrix = Matrix::<[u8; 4]>::with_width_and_height(3, 2);
+        matrix.array_view3_mut()[[1, 0, 3]] = 0xff;
+        assert_eq!(matrix[(0, 1)], [0, 0, 0, 0xff]);
+
+        let mut gray = Matrix::<u16>::with_width_and_height(3, 2);
+        gray.array_view_mut()[[1, 2]] = 7;
+        assert_eq!(gray[(2, 1)], 7);
+        assert_eq!(gray.array_view().sum(), 7);
+    }
+
+    #[test]
+    fn strided_views() {
+        let mut matrix = Matrix::<u16>::with_width_and_height(3, 2);
+        for (idx, p) in matrix.as_mut_slice().iter_mut().enumerate() {
+            *p = idx as u16;
+        }
+
+        let canvas = ByteCanvasRef::from(&matrix).flipped_vertically();
+        let view = canvas.array_view::<u16>().unwrap();
+        assert_eq!(view.dim(), (2, 3));
+        assert_eq!(view[[0, 1]], 4);
+
+        let view = canvas.as_ref().transposed().array_view::<u16>().unwrap();
+        assert_eq!(view.dim(), (3, 2));
+        assert_eq!(view[[2, 0]], 5);
+
+        assert!(canvas.array_view::<u8>().is_none());
+        assert!(canvas.array_view::<u32>().is_none());
+
+        let mut canvas = ByteCanvasMut::from(&mut matrix).flipped_horizontally();
+        canvas.array_view_mut::<u16>().unwrap()[[0, 0]] = 42;
+        assert_eq!(matrix[(2, 0)], 42);
+
+        // Rows that overlap each other can only be viewed immutably.
+        let element = crate::layout::Element::from_pixel::<u16>();
+        let spec = StrideSpec {
+            width: 2,
+            height: 2,
+            element,
+            width_stride: 2,
+            height_stride: 2,
+            offset: 0,
+        };
+        let layout = StrideLayout::new(spec).unwrap();
+        let mut bytes = [0u16; 3];
+        let bytes: &mut [u8] = bytemuck::cast_slice_mut(&mut bytes);
+        assert!(ByteCanvasRef::with_bytes(layout, bytes)
+            .unwrap()
+            .array_view::<u16>()
+            .is_some());
+        assert!(ByteCanvasMut::with_bytes(layout, bytes)
+            .unwrap()
+            .array_view_mut::<u16>()
+            .is_none());
+    }
+
+    #[test]
+    fn arrays_into_matrices() {
+        let array = Array3::from_shape_fn((2, 3, 4), |(y, x, c)| (y * 100 + x * 10 + c) as u8);
+        let matrix = Matrix::<[u8; 4]>::try_from(array.view()).unwrap();
+        assert_eq!(matrix[(2, 1)], [120, 121, 122, 123]);
+        assert!(Matrix::<[u8; 3]>::try_from(array.view()).is_err());
+
+        let array = Array2::from_shape_fn((4, 4), |(y, x)| (y * 4 + x) as u32);
+        let matrix = Matrix::<u32>::try_from(array.slice(s![..;2, 1..])).unwrap();
+        assert_eq!(matrix.array_view(), array.slice(s![..;2, 1..]));
+    }
+}
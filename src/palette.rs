@@ -0,0 +1,539 @@
+//! Indexed color images with an attached palette.
+//!
+//! An [`IndexedMatrix`] stores one byte per pixel, an index into a palette of up to 256 colors.
+//! This is the pixel data of a [`FourCC::C8`](crate::drm::FourCC::C8) frame buffer or of a GIF
+//! image. It can be expanded into a matrix of colors in bulk. The reverse, choosing a palette for
+//! an image of colors, is done by a [`Quantizer`].
+//!
+//! ```
+//! use canvas::Matrix;
+//! use canvas::palette::{Method, Quantizer};
+//!
+//! let mut image = Matrix::<[u8; 4]>::with_width_and_height(4, 4);
+//! image[(3, 3)] = [0xff, 0x00, 0x00, 0xff];
+//!
+//! let indexed = Quantizer::new(Method::MedianCut).with_colors(2).quantize(&image);
+//! assert_eq!(indexed.palette().len(), 2);
+//! assert_eq!(indexed.expand(), image);
+//! ```
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::{Layout, Matrix, Pixel};
+
+/// An image of palette indices together with its palette.
+///
+/// Indices that are not covered by the palette expand to the default color, transparent black.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IndexedMatrix<E> {
+    indices: Matrix<u8>,
+    palette: Vec<E>,
+}
+
+/// A color that can be an entry of a palette.
+///
+/// This is implemented for RGBA colors with 8 and 16 bits per channel.
+pub trait PaletteEntry: Copy + Default + PartialEq {
+    /// The pixel describing the entry type.
+    const PIXEL: Pixel<Self>;
+    /// The largest value of a channel.
+    const MAX: i32;
+    /// The value of all channels.
+    fn to_channels(self) -> [i32; 4];
+    /// Create a color from channel values, saturating at the bounds of the channel type.
+    fn from_channels(channels: [i32; 4]) -> Self;
+}
+
+/// The algorithm choosing the colors of a palette.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Method {
+    /// Repeatedly split the box of colors with the largest extent along one channel at its median.
+    MedianCut,
+    /// Merge the least significant bits of colors in a tree until few enough colors remain.
+    ///
+    /// The tree has one level for each bit of a channel, 8 or 16.
+    Octree,
+}
+
+/// The configuration for reducing an image to a palette.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Quantizer {
+    method: Method,
+    colors: usize,
+    dither: bool,
+}
+
+impl<E: PaletteEntry> IndexedMatrix<E> {
+    /// Combine indices with a palette.
+    ///
+    /// Returns `None` if the palette has more than 256 entries.
+    pub fn new(indices: Matrix<u8>, palette: Vec<E>) -> Option<Self> {
+        if palette.len() > 256 {
+            return None;
+        }
+
+        Some(IndexedMatrix { indices, palette })
+    }
+
+    /// Get the palette indices.
+    pub fn indices(&self) -> &Matrix<u8> {
+        &self.indices
+    }
+
+    /// Get the palette indices mutably.
+    pub fn indices_mut(&mut self) -> &mut Matrix<u8> {
+        &mut self.indices
+    }
+
+    /// Get the colors of the palette.
+    pub fn palette(&self) -> &[E] {
+        &self.palette
+    }
+
+    /// Get the colors of the palette mutably.
+    pub fn palette_mut(&mut self) -> &mut [E] {
+        &mut self.palette
+    }
+
+    /// Split into the indices and the palette.
+    pub fn into_parts(self) -> (Matrix<u8>, Vec<E>) {
+        (self.indices, self.palette)
+    }
+
+    /// Replace every index with its color.
+    ///
+    /// # Panics
+    ///
+    /// This method panics when the allocation of the matrix fails.
+    pub fn expand(&self) -> Matrix<E> {
+        let layout = self.indices.layout();
+        let layout = Layout::width_and_height_for_pixel(E::PIXEL, layout.width(), layout.height())
+            .expect("Layout of same size as indices");
+
+        let mut colors = Matrix::with_layout(layout);
+        for (color, &index) in colors
+            .as_mut_slice()
+            .iter_mut()
+            .zip(self.indices.as_slice())
+        {
+            *color = self
+                .palette
+                .get(usize::from(index))
+                .copied()
+                .unwrap_or_default();
+        }
+
+        colors
+    }
+}
+
+impl Quantizer {
+    /// Create a quantizer for palettes of 256 colors without dithering.
+    pub fn new(method: Method) -> Self {
+        Quantizer {
+            method,
+            colors: 256,
+            dither: false,
+        }
+    }
+
+    /// Change the maximum number of colors, clamped to the range from 1 to 256.
+    #[must_use]
+    pub fn with_colors(self, colors: usize) -> Self {
+        Quantizer {
+            colors: colors.clamp(1, 256),
+            ..self
+        }
+    }
+
+    /// Enable or disable Floyd-Steinberg dithering of the indices.
+    #[must_use]
+    pub fn with_dithering(self, dither: bool) -> Self {
+        Quantizer { dither, ..self }
+    }
+
+    /// Choose a palette for an image and map all pixels to it.
+    ///
+    /// Images with few enough distinct colors are reproduced exactly. An empty image results in an
+    /// empty palette.
+    ///
+    /// # Panics
+    ///
+    /// This method panics when the allocation of the indices fails.
+    pub fn quantize<E: PaletteEntry>(&self, image: &Matrix<E>) -> IndexedMatrix<E> {
+        let pixels = image.as_slice();
+        let palette: Vec<[i32; 4]> = match self.method {
+            Method::MedianCut => {
+                let colors = pixels.iter().map(|p| p.to_channels()).collect();
+                median_cut(colors, self.colors)
+            }
+            Method::Octree => {
+                let mut tree = Octree::new(self.colors, E::MAX);
+                pixels.iter().for_each(|p| tree.insert(p.to_channels()));
+                tree.palette()
+            }
+        };
+
+        let layout = image.layout();
+        let mut indices = Matrix::<u8>::with_width_and_height(layout.width(), layout.height());
+        if self.dither {
+            dither(image, &palette, &mut indices, E::MAX);
+        } else {
+            for (index, pixel) in indices.as_mut_slice().iter_mut().zip(pixels) {
+                *index = nearest(&palette, pixel.to_channels());
+            }
+        }
+
+        IndexedMatrix {
+            indices,
+            palette: palette.into_iter().map(E::from_channels).collect(),
+        }
+    }
+}
+
+/// The index of the palette entry closest to a color.
+fn nearest(palette: &[[i32; 4]], color: [i32; 4]) -> u8 {
+    let distance = |entry: &[i32; 4]| -> i64 {
+        entry
+            .iter()
+            .zip(&color)
+            .map(|(&a, &b)| i64::from(a - b) * i64::from(a - b))
+            .sum()
+    };
+
+    let (idx, _) = palette
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, entry)| distance(entry))
+        .unwrap_or((0, &[0; 4]));
+    idx as u8
+}
+
+/// Map all pixels while diffusing the quantization error onto their unvisited neighbours.
+fn dither<E: PaletteEntry>(
+    image: &Matrix<E>,
+    palette: &[[i32; 4]],
+    indices: &mut Matrix<u8>,
+    max: i32,
+) {
+    let layout = image.layout();
+    let width = layout.width();
+    // Errors scaled by 16, with one column of padding on each side.
+    let mut current = Vec::new();
+    current.resize(width + 2, [0i32; 4]);
+    let mut next = current.clone();
+
+    for y in 0..layout.height() {
+        for x in 0..width {
+            let mut target = image[(x, y)].to_channels();
+            for (t, e) in target.iter_mut().zip(&current[x + 1]) {
+                *t = (*t + e / 16).clamp(0, max);
+            }
+
+            let index = nearest(palette, target);
+            indices[(x, y)] = index;
+
+            let chosen = palette.get(usize::from(index)).copied().unwrap_or_default();
+            for c in 0..4 {
+                let error = target[c] - chosen[c];
+                current[x + 2][c] += error * 7;
+                next[x][c] += error * 3;
+                next[x + 1][c] += error * 5;
+                next[x + 2][c] += error;
+            }
+        }
+
+        core::mem::swap(&mut current, &mut next);
+        next.iter_mut().for_each(|e| *e = [0; 4]);
+    }
+}
+
+/// Split boxes of distinct colors, weighted by the number of their pixels.
+fn median_cut(mut colors: Vec<[i32; 4]>, count: usize) -> Vec<[i32; 4]> {
+    colors.sort_unstable();
+    let mut weighted: Vec<([i32; 4], i64)> = Vec::new();
+    for color in colors {
+        match weighted.last_mut() {
+            Some((last, weight)) if *last == color => *weight += 1,
+            _ => weighted.push((color, 1)),
+        }
+    }
+
+    if weighted.is_empty() {
+        return Vec::new();
+    }
+
+    // The extent of a box and the channel along which it is largest.
+    let extent = |colors: &[([i32; 4], i64)]| -> (i32, usize) {
+        (0..4)
+            .map(|c| {
+                let min = colors.iter().map(|p| p.0[c]).min().unwrap_or(0);
+                let max = colors.iter().map(|p| p.0[c]).max().unwrap_or(0);
+                (max - min, c)
+            })
+            .max()
+            .unwrap()
+    };
+
+    let mut boxes: Vec<Range<usize>> = alloc::vec![0..weighted.len()];
+    while boxes.len() < count {
+        let (largest, channel, idx) = boxes
+            .iter()
+            .enumerate()
+            .map(|(idx, range)| {
+                let (largest, channel) = extent(&weighted[range.clone()]);
+                (largest, channel, idx)
+            })
+            .max()
+            .unwrap();
+
+        if largest == 0 {
+            break;
+        }
+
+        let range = boxes[idx].clone();
+        let colors = &mut weighted[range.clone()];
+        colors.sort_unstable_by_key(|p| p.0[channel]);
+
+        // Split after the color containing the median pixel. A box with a non-zero extent has at
+        // least two distinct colors, so both halves can be kept non-empty.
+        let total: i64 = colors.iter().map(|p| p.1).sum();
+        let mut seen = 0;
+        let median = colors
+            .iter()
+            .position(|p| {
+                seen += p.1;
+                2 * seen >= total
+            })
+            .unwrap();
+        let split = range.start + (median + 1).min(colors.len() - 1);
+
+        boxes[idx] = range.start..split;
+        boxes.push(split..range.end);
+    }
+
+    boxes
+        .into_iter()
+        .map(|range| {
+            let (mut sum, mut count) = ([0i64; 4], 0);
+            for (color, weight) in &weighted[range] {
+                sum.iter_mut()
+                    .zip(color)
+                    .for_each(|(s, &c)| *s += i64::from(c) * weight);
+                count += weight;
+            }
+            average(sum, count)
+        })
+        .collect()
+}
+
+fn average(sum: [i64; 4], count: i64) -> [i32; 4] {
+    sum.map(|s| ((s + count / 2) / count) as i32)
+}
+
+/// A tree over the bits of all four channels, from the most significant one.
+struct Octree {
+    nodes: Vec<Node>,
+    /// Nodes with children, by their depth.
+    reducible: Vec<Vec<usize>>,
+    leaves: usize,
+    colors: usize,
+    /// The number of bits of a channel, the depth of the leaves.
+    bits: u32,
+}
+
+#[derive(Default)]
+struct Node {
+    children: [usize; 16],
+    count: i64,
+    sum: [i64; 4],
+    leaf: bool,
+}
+
+impl Octree {
+    fn new(colors: usize, max: i32) -> Self {
+        let bits = 32 - max.leading_zeros();
+        let mut reducible = alloc::vec![Vec::new(); bits as usize];
+        reducible[0].push(0);
+
+        Octree {
+            nodes: alloc::vec![Node::default()],
+            reducible,
+            leaves: 0,
+            colors,
+            bits,
+        }
+    }
+
+    fn insert(&mut self, color: [i32; 4]) {
+        let mut node = 0;
+        for depth in 0..self.bits {
+            if self.nodes[node].leaf {
+                break;
+            }
+
+            let bit = self.bits - 1 - depth;
+            let child = (0..4)
+                .map(|c| ((color[c] >> bit) as usize & 1) << c)
+                .sum::<usize>();
+
+            if self.nodes[node].children[child] == 0 {
+                let new = self.nodes.len();
+                let leaf = depth == self.bits - 1;
+                self.nodes.push(Node {
+                    leaf,
+                    ..Node::default()
+                });
+
+                if leaf {
+                    self.leaves += 1;
+                } else {
+                    self.reducible[depth as usize + 1].push(new);
+                }
+
+                self.nodes[node].children[child] = new;
+            }
+
+            node = self.nodes[node].children[child];
+        }
+
+        let node = &mut self.nodes[node];
+        node.count += 1;
+        node.sum
+            .iter_mut()
+            .zip(&color)
+            .for_each(|(s, &c)| *s += i64::from(c));
+
+        while self.leaves > self.colors {
+            self.reduce();
+        }
+    }
+
+    /// Merge the children of one of the deepest nodes, which are all leaves.
+    fn reduce(&mut self) {
+        let node = match self.reducible.iter_mut().rev().find_map(Vec::pop) {
+            Some(node) => node,
+            None => return,
+        };
+
+        let children = core::mem::take(&mut self.nodes[node].children);
+        let (mut count, mut sum) = (0, [0; 4]);
+        for &child in children.iter().filter(|&&child| child != 0) {
+            // The merged child is no longer part of the tree.
+            let child = core::mem::take(&mut self.nodes[child]);
+            count += child.count;
+            sum.iter_mut().zip(&child.sum).for_each(|(s, &c)| *s += c);
+            self.leaves -= 1;
+        }
+
+        let node = &mut self.nodes[node];
+        node.count = count;
+        node.sum = sum;
+        node.leaf = true;
+        self.leaves += 1;
+    }
+
+    fn palette(&self) -> Vec<[i32; 4]> {
+        self.nodes
+            .iter()
+            .filter(|node| node.leaf && node.count > 0)
+            .map(|node| average(node.sum, node.count))
+            .collect()
+    }
+}
+
+impl PaletteEntry for [u8; 4] {
+    const PIXEL: Pixel<Self> = crate::pixel::constants::RGBA;
+    const MAX: i32 = 0xff;
+
+    fn to_channels(self) -> [i32; 4] {
+        self.map(i32::from)
+    }
+
+    fn from_channels(channels: [i32; 4]) -> Self {
+        channels.map(|c| c.clamp(0, Self::MAX) as u8)
+    }
+}
+
+impl PaletteEntry for [u16; 4] {
+    const PIXEL: Pixel<Self> = crate::pixel::constants::U16.array4();
+    const MAX: i32 = 0xffff;
+
+    fn to_channels(self) -> [i32; 4] {
+        self.map(i32::from)
+    }
+
+    fn from_channels(channels: [i32; 4]) -> Self {
+        channels.map(|c| c.clamp(0, Self::MAX) as u16)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gradient() -> Matrix<[u8; 4]> {
+        let mut image = Matrix::<[u8; 4]>::with_width_and_height(64, 4);
+        for x in 0..64 {
+            for y in 0..4 {
+                image[(x, y)] = [(x * 4) as u8, (y * 60) as u8, 0x80, 0xff];
+            }
+        }
+        image
+    }
+
+    #[test]
+    fn exact_palettes() {
+        let image = gradient();
+        for &method in &[Method::MedianCut, Method::Octree] {
+            let indexed = Quantizer::new(method).quantize(&image);
+            assert_eq!(indexed.palette().len(), 256, "{:?}", method);
+            assert_eq!(indexed.expand(), image, "{:?}", method);
+        }
+    }
+
+    #[test]
+    fn reduced_palettes() {
+        let image = gradient();
+        for &method in &[Method::MedianCut, Method::Octree] {
+            for &dither in &[false, true] {
+                let quantizer = Quantizer::new(method)
+                    .with_colors(16)
+                    .with_dithering(dither);
+                let indexed = quantizer.quantize(&image);
+                assert!(indexed.palette().len() <= 16, "{:?}", method);
+
+                // Dithering keeps the average color close to the original.
+                let sum = |m: &Matrix<[u8; 4]>| -> i64 {
+                    m.as_slice().iter().map(|p| i64::from(p[0])).sum()
+                };
+                let difference = (sum(&image) - sum(&indexed.expand())).abs();
+                let tolerance = if dither { 256 } else { 256 * 16 };
+                assert!(difference <= tolerance, "{:?} {}", method, difference);
+            }
+        }
+    }
+
+    #[test]
+    fn wide_entries() {
+        let pixel = crate::pixel::constants::U16.array4();
+        let layout = Layout::width_and_height_for_pixel(pixel, 2, 1).unwrap();
+        let mut image = Matrix::with_layout(layout);
+        image[(0, 0)] = [0xffff, 0x1234, 0, 0xffff];
+
+        let indexed = Quantizer::new(Method::Octree).quantize(&image);
+        assert_eq!(indexed.expand(), image);
+
+        // Colors that differ only in their low byte are still distinct.
+        image[(0, 0)] = [0x0100, 0x0100, 0x0100, 0xffff];
+        image[(1, 0)] = [0x0101, 0x0100, 0x0100, 0xffff];
+        for &method in &[Method::MedianCut, Method::Octree] {
+            let indexed = Quantizer::new(method).quantize(&image);
+            assert_eq!(indexed.palette().len(), 2, "{:?}", method);
+            assert_eq!(indexed.expand(), image, "{:?}", method);
+        }
+
+        let indices = indexed.indices().clone();
+        assert!(IndexedMatrix::new(indices, alloc::vec![[0u16; 4]; 257]).is_none());
+    }
+}
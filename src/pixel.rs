@@ -4,10 +4,13 @@
 #![allow(unsafe_code)]
 
 use core::cmp::{Eq, Ord, Ordering, PartialEq, PartialOrd};
+use core::convert::TryFrom;
 use core::marker::PhantomData;
 use core::{fmt, hash, mem, ptr, slice};
 
 use crate::buf::buf;
+use crate::endian::{BigEndian, LittleEndian};
+use crate::texel::Texel;
 
 /// Marker struct to denote a pixel type.
 ///
@@ -45,6 +48,13 @@ pub trait AsPixel {
     /// any invalid type. This trait should only be implemented when you know for sure that the
     /// type is correct.
     fn pixel() -> Pixel<Self>;
+
+    /// Describe the channels of this type, if they have a known meaning.
+    ///
+    /// This is advisory information and by default no description is given.
+    fn texel() -> Option<Texel> {
+        None
+    }
 }
 
 pub(crate) const MAX_ALIGN: usize = 16;
@@ -60,37 +70,101 @@ pub struct MaxAligned(pub(crate) [u8; 16]);
 unsafe impl bytemuck::Zeroable for MaxAligned {}
 unsafe impl bytemuck::Pod for MaxAligned {}
 
+// The endian wrappers are transparent, so they are `Pod` exactly if their content is.
+unsafe impl<T: bytemuck::Zeroable> bytemuck::Zeroable for BigEndian<T> {}
+unsafe impl<T: bytemuck::Pod> bytemuck::Pod for BigEndian<T> {}
+unsafe impl<T: bytemuck::Zeroable> bytemuck::Zeroable for LittleEndian<T> {}
+unsafe impl<T: bytemuck::Pod> bytemuck::Pod for LittleEndian<T> {}
+
 pub(crate) mod constants {
-    use super::{AsPixel, MaxAligned, Pixel};
+    use super::{AsPixel, BigEndian, LittleEndian, MaxAligned, Pixel};
+    use crate::texel::{Channels, SampleKind, Texel};
 
     macro_rules! constant_pixels {
-        ($(($name:ident, $type:ty)),*) => {
+        ($(($name:ident, $type:ty, $texel:expr)),*) => {
             $(pub const $name: Pixel<$type> = Pixel(core::marker::PhantomData) ;
               impl AsPixel for $type {
                   fn pixel() -> Pixel<Self> {
                       $name
                   }
+
+                  fn texel() -> Option<Texel> {
+                      $texel
+                  }
               }
               )*
         }
     }
 
+    const fn scalar(kind: SampleKind, bits: u8) -> Option<Texel> {
+        Some(Texel::uniform(kind, Channels::Unspecified, 1, bits))
+    }
+
     constant_pixels!(
-        (EMPTY, ()),
-        (I8, i8),
-        (U8, u8),
-        (I16, i16),
-        (U16, u16),
-        (I32, i32),
-        (U32, u32),
-        (F32, f32),
-        (I64, i64),
-        (U64, u64),
-        (F64, f64),
-        (RGB, [u8; 3]),
-        (RGBA, [u8; 4]),
-        (MAX, MaxAligned)
+        (EMPTY, (), None),
+        (I8, i8, scalar(SampleKind::SInt, 8)),
+        (U8, u8, scalar(SampleKind::UInt, 8)),
+        (I16, i16, scalar(SampleKind::SInt, 16)),
+        (U16, u16, scalar(SampleKind::UInt, 16)),
+        (I32, i32, scalar(SampleKind::SInt, 32)),
+        (U32, u32, scalar(SampleKind::UInt, 32)),
+        (F32, f32, scalar(SampleKind::Float, 32)),
+        (I64, i64, scalar(SampleKind::SInt, 64)),
+        (U64, u64, scalar(SampleKind::UInt, 64)),
+        (F64, f64, scalar(SampleKind::Float, 64)),
+        (BE_U16, BigEndian<u16>, scalar(SampleKind::UInt, 16)),
+        (BE_U32, BigEndian<u32>, scalar(SampleKind::UInt, 32)),
+        (BE_F32, BigEndian<f32>, scalar(SampleKind::Float, 32)),
+        (LE_U16, LittleEndian<u16>, scalar(SampleKind::UInt, 16)),
+        (LE_U32, LittleEndian<u32>, scalar(SampleKind::UInt, 32)),
+        (LE_F32, LittleEndian<f32>, scalar(SampleKind::Float, 32)),
+        (MAX, MaxAligned, None)
     );
+
+    // Arrays are covered by a generic implementation of `AsPixel`, these only name them.
+    pub const GRAY_ALPHA: Pixel<[u8; 2]> = U8.array2();
+    pub const RGB: Pixel<[u8; 3]> = U8.array3();
+    pub const RGBA: Pixel<[u8; 4]> = U8.array4();
+    pub const GRAY_ALPHA16: Pixel<[u16; 2]> = U16.array2();
+    pub const RGB16: Pixel<[u16; 3]> = U16.array3();
+    pub const RGBA16: Pixel<[u16; 4]> = U16.array4();
+    pub const GRAY_ALPHA_F32: Pixel<[f32; 2]> = F32.array2();
+    pub const RGB_F32: Pixel<[f32; 3]> = F32.array3();
+    pub const RGBA_F32: Pixel<[f32; 4]> = F32.array4();
+}
+
+impl<T: AsPixel, const N: usize> AsPixel for [T; N] {
+    fn pixel() -> Pixel<Self> {
+        T::pixel().array()
+    }
+
+    /// Arrays of two, three and four scalars are assumed to be gray with alpha, RGB and RGBA, where
+    /// unsigned integers are normalized. This matches the constants such as `GRAY_ALPHA` and `RGBA`
+    /// but arrays in other orders, such as BGRA, must be described by a wrapper type instead.
+    /// Single element arrays keep the description of their scalar.
+    fn texel() -> Option<Texel> {
+        use crate::texel::{Channels, SampleKind};
+
+        let scalar = T::texel()?;
+        if scalar.channels != Channels::Unspecified || scalar.channel_count() != 1 {
+            return None;
+        }
+
+        let count = u8::try_from(N).ok().filter(|n| (1..=4).contains(n))?;
+        let channels = match count {
+            2 => Channels::GrayAlpha,
+            3 => Channels::Rgb,
+            4 => Channels::Rgba,
+            _ => return Some(scalar),
+        };
+
+        let kind = match scalar.kind {
+            SampleKind::UInt => SampleKind::UNorm,
+            kind => kind,
+        };
+
+        Some(Texel::uniform(kind, channels, count, scalar.bits[0]))
+    }
 }
 
 impl<P: bytemuck::Pod> Pixel<P> {
@@ -104,6 +178,23 @@ impl<P: bytemuck::Pod> Pixel<P> {
             None
         }
     }
+
+    /// Construct an instance of the marker in a constant context.
+    ///
+    /// This is the checked counterpart of `new_unchecked` for use in constants, such as those
+    /// generated by `#[derive(AsPixel)]`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the alignment of `P` is larger than that of [`MaxAligned`]. When
+    /// evaluated in a constant this is a compile time error instead.
+    pub const fn for_pod() -> Self {
+        assert!(
+            mem::align_of::<P>() <= MAX_ALIGN,
+            "Alignment larger than `MaxAligned`"
+        );
+        Pixel(PhantomData)
+    }
 }
 
 impl<P, O: bytemuck::TransparentWrapper<P>> IsTransparentWrapper<P, O> {
@@ -171,44 +262,60 @@ impl<P> Pixel<P> {
     // code here to pad our stats but they are not checked by the type system so it's risky. Better
     // explain their safety in the code as comments.
 
-    /// Construct a pixel as an array of no elements.
-    pub const fn array0(self) -> Pixel<[P; 0]> {
+    /// Construct a pixel as an array of `N` elements.
+    ///
+    /// ```
+    /// use canvas::{AsPixel, Matrix};
+    /// use canvas::pixels::F32;
+    ///
+    /// const FEATURES: canvas::Pixel<[f32; 16]> = F32.array();
+    /// assert_eq!(FEATURES.size(), 64);
+    ///
+    /// // Arrays of any length implement `AsPixel` as well.
+    /// let matrix = Matrix::<[f32; 16]>::with_width_and_height(2, 2);
+    /// assert_eq!(matrix.as_bytes().len(), 256);
+    /// assert_eq!(<[f32; 16]>::pixel(), FEATURES);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the size of the array does not fit into an `isize`. When evaluated
+    /// in a constant this is a compile time error instead.
+    pub const fn array<const N: usize>(self) -> Pixel<[P; N]> {
+        let fits = match mem::size_of::<P>().checked_mul(N) {
+            Some(size) => size <= isize::MAX as usize,
+            None => false,
+        };
+        assert!(fits, "Array size larger than `isize::MAX`");
         // Safety:
-        // * has no validity/safety invariants
+        // * has validity/safety invariants of P, none, and arrays have no padding
         // * has the same alignment as P which is not larger then MaxAligned
         unsafe { Pixel::new_unchecked() }
     }
 
+    /// Construct a pixel as an array of no elements.
+    pub const fn array0(self) -> Pixel<[P; 0]> {
+        self.array()
+    }
+
     /// Construct a pixel as an array of one element.
     pub const fn array1(self) -> Pixel<[P; 1]> {
-        // Safety:
-        // * has validity/safety invariants of P, none
-        // * has the same alignment as P which is not larger then MaxAligned
-        unsafe { Pixel::new_unchecked() }
+        self.array()
     }
 
     /// Construct a pixel as an array of two elements.
     pub const fn array2(self) -> Pixel<[P; 2]> {
-        // Safety:
-        // * has validity/safety invariants of P, none
-        // * has the same alignment as P which is not larger then MaxAligned
-        unsafe { Pixel::new_unchecked() }
+        self.array()
     }
 
     /// Construct a pixel as an array of three elements.
     pub const fn array3(self) -> Pixel<[P; 3]> {
-        // Safety:
-        // * has validity/safety invariants of P, none
-        // * has the same alignment as P which is not larger then MaxAligned
-        unsafe { Pixel::new_unchecked() }
+        self.array()
     }
 
     /// Construct a pixel as an array of four elements.
     pub const fn array4(self) -> Pixel<[P; 4]> {
-        // Safety:
-        // * has validity/safety invariants of P, none
-        // * has the same alignment as P which is not larger then MaxAligned
-        unsafe { Pixel::new_unchecked() }
+        self.array()
     }
 
     /// Construct a pixel by wrapping into a transparent wrapper.
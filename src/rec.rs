@@ -6,6 +6,7 @@ use core::fmt;
 use core::ops::{Deref, DerefMut};
 
 use crate::buf::{buf, Buffer};
+use crate::endian::SwapBytes;
 use crate::{AsPixel, Pixel};
 
 /// A **r**einterpretable v**ec**tor for an array of pixels.
@@ -241,6 +242,16 @@ impl<P> Rec<P> {
         self.buf_mut().as_mut_pixels(pixel)
     }
 
+    /// Reverse the byte order of all samples in place.
+    pub fn swap_bytes(&mut self)
+    where
+        P: SwapBytes,
+    {
+        self.as_mut_slice()
+            .iter_mut()
+            .for_each(|p| *p = p.swap_bytes())
+    }
+
     /// The number of accessible elements for the current type.
     pub fn len(&self) -> usize {
         self.as_slice().len()
@@ -0,0 +1,565 @@
+//! Resampling of matrices to different dimensions.
+//!
+//! All filters are separable. The image is first scaled horizontally into an intermediate buffer
+//! of floating point channels, then vertically into the destination. Coordinates outside the
+//! source are clamped, i.e. they are treated as copies of the closest edge pixel, which avoids
+//! darkening or brightening the borders of the image.
+//!
+//! The destination is provided by the caller. Its dimensions determine the scale so that a buffer
+//! can be reused across frames with [`Matrix::reuse`].
+//!
+//! ```
+//! use canvas::{Layout, Matrix};
+//! use canvas::resample::{resample, Filter};
+//!
+//! let frame = Matrix::<[u8; 4]>::with_width_and_height(640, 480);
+//! let mut input = Matrix::<[u8; 4]>::default();
+//!
+//! let layout = Layout::width_and_height(224, 224).unwrap();
+//! if input.reuse(layout).is_err() {
+//!     input.resize(layout);
+//! }
+//!
+//! resample(&frame, &mut input, Filter::Bilinear);
+//! ```
+//!
+//! [`Matrix::reuse`]: ../struct.Matrix.html#method.reuse
+use alloc::vec::Vec;
+use core::f32::consts::PI;
+use core::marker::PhantomData;
+
+use crate::metrics;
+use crate::transfer::{TransferFunction, TransferSample};
+use crate::Matrix;
+
+/// The filter kernel used to compute each output pixel from its neighbourhood.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Filter {
+    /// Choose the closest source pixel.
+    Nearest,
+    /// Interpolate linearly between the closest pixels, a triangle filter.
+    Bilinear,
+    /// The cubic convolution kernel of Keys with `a = -0.5`, also called Catmull-Rom.
+    Bicubic,
+    /// A sinc filter windowed by the central lobe of a sinc three times as wide.
+    Lanczos3,
+    /// Average the source pixels covered by each destination pixel, the classic mipmap filter.
+    Box,
+    /// A sinc filter with a radius of three, windowed by a Kaiser window with `alpha = 4`.
+    ///
+    /// This keeps more detail than a box filter when reducing an image, for example for mipmaps.
+    Kaiser,
+}
+
+/// A channel type that can be resampled.
+///
+/// Values are converted to `f32` for filtering. Integer types round to the nearest value and
+/// saturate at their bounds when converted back. The peak of the sample normalizes values for
+/// transfer functions.
+pub trait Sample: metrics::Sample + TransferSample {
+    fn to_f32(self) -> f32;
+    fn from_f32(val: f32) -> Self;
+}
+
+/// A pixel whose channels can be resampled independently.
+///
+/// This is implemented for all [`Sample`] types and for arrays of them.
+pub trait Resample: Copy {
+    /// The type of each channel.
+    type Sample: Sample;
+    /// The number of channels of the pixel.
+    const CHANNELS: usize;
+    /// The samples of all channels, in order.
+    fn samples(&self) -> &[Self::Sample];
+    /// Create a pixel from the channel values in a slice of length `CHANNELS`.
+    fn store(from: &[f32]) -> Self;
+}
+
+/// Precomputed filter weights for resampling between two fixed sizes.
+///
+/// Prefer this over [`resample`] when many images of the same dimensions are scaled, for example
+/// every frame of a video, as the filter weights are only computed once.
+#[derive(Clone, Debug)]
+pub struct Resampler {
+    horizontal: Weights,
+    vertical: Weights,
+}
+
+/// Converts samples between their encoding and linear light, keeping their peak value.
+///
+/// Integer samples are decoded with a table of all their values. Build this once to filter
+/// several images, such as all levels of a mipmap chain.
+#[derive(Clone, Debug)]
+pub(crate) struct Linearize<S> {
+    function: TransferFunction,
+    table: Vec<f32>,
+    sample: PhantomData<S>,
+}
+
+/// The contributions of source samples to each destination sample along one axis.
+#[derive(Clone, Debug)]
+struct Weights {
+    src_len: usize,
+    /// For each destination index, the first source index and the range into `weights`.
+    taps: Vec<(usize, usize, usize)>,
+    weights: Vec<f32>,
+}
+
+impl Filter {
+    /// The radius of the kernel, outside of which it is zero.
+    fn support(self) -> f32 {
+        match self {
+            Filter::Nearest => 0.5,
+            Filter::Bilinear => 1.0,
+            Filter::Bicubic => 2.0,
+            Filter::Lanczos3 => 3.0,
+            Filter::Box => 0.5,
+            Filter::Kaiser => 3.0,
+        }
+    }
+
+    fn kernel(self, x: f32) -> f32 {
+        let x = x.abs();
+        match self {
+            Filter::Nearest => {
+                if x < 0.5 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Filter::Bilinear => (1.0 - x).max(0.0),
+            Filter::Bicubic => {
+                const A: f32 = -0.5;
+                if x < 1.0 {
+                    ((A + 2.0) * x - (A + 3.0)) * x * x + 1.0
+                } else if x < 2.0 {
+                    ((A * x - 5.0 * A) * x + 8.0 * A) * x - 4.0 * A
+                } else {
+                    0.0
+                }
+            }
+            Filter::Lanczos3 => {
+                if x < 1e-6 {
+                    1.0
+                } else if x < 3.0 {
+                    let pix = PI * x;
+                    3.0 * libm::sinf(pix) * libm::sinf(pix / 3.0) / (pix * pix)
+                } else {
+                    0.0
+                }
+            }
+            Filter::Box => {
+                // Pixels on the border of the box are split between both neighbours.
+                if x < 0.5 {
+                    1.0
+                } else if x == 0.5 {
+                    0.5
+                } else {
+                    0.0
+                }
+            }
+            Filter::Kaiser => {
+                const ALPHA: f32 = 4.0;
+                if x < 1e-6 {
+                    1.0
+                } else if x < 3.0 {
+                    let pix = PI * x;
+                    let t = x / 3.0;
+                    let window = bessel_i0(ALPHA * libm::sqrtf(1.0 - t * t)) / bessel_i0(ALPHA);
+                    libm::sinf(pix) / pix * window
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+/// The modified Bessel function of the first kind and order zero, by its power series.
+fn bessel_i0(x: f32) -> f32 {
+    let (mut sum, mut term, quarter) = (1.0, 1.0, x * x / 4.0);
+    for k in 1..32 {
+        term *= quarter / (k * k) as f32;
+        sum += term;
+        if term < sum * 1e-8 {
+            break;
+        }
+    }
+    sum
+}
+
+impl<S: Sample> Linearize<S> {
+    pub(crate) fn new(function: TransferFunction) -> Self {
+        let direct = Linearize {
+            function,
+            table: Vec::new(),
+            sample: PhantomData,
+        };
+
+        let table = (0..S::TABLE_LEN)
+            .map(|idx| direct.decode(S::from_index(idx)))
+            .collect();
+        Linearize { table, ..direct }
+    }
+
+    fn peak() -> f32 {
+        <S as metrics::Sample>::PEAK as f32
+    }
+
+    fn decode(&self, sample: S) -> f32 {
+        match self.table.get(sample.index()) {
+            Some(&linear) => linear,
+            None => self.function.to_linear(sample.to_f32() / Self::peak()) * Self::peak(),
+        }
+    }
+
+    fn encode(&self, linear: f32) -> f32 {
+        self.function.from_linear(linear / Self::peak()) * Self::peak()
+    }
+}
+
+impl Weights {
+    fn new(filter: Filter, src_len: usize, dst_len: usize) -> Self {
+        assert!(
+            src_len > 0 || dst_len == 0,
+            "Can not resample an empty image to a non-empty one"
+        );
+
+        let mut taps = Vec::with_capacity(dst_len);
+        let mut weights = Vec::new();
+        let scale = src_len as f32 / dst_len as f32;
+        let last = src_len.saturating_sub(1);
+
+        for i in 0..dst_len {
+            // The position of the destination pixel center in source coordinates.
+            let center = (i as f32 + 0.5) * scale;
+
+            if let Filter::Nearest = filter {
+                let idx = (center as usize).min(last);
+                taps.push((idx, weights.len(), weights.len() + 1));
+                weights.push(1.0);
+                continue;
+            }
+
+            // When shrinking, widen the kernel so that every source pixel contributes.
+            let stretch = scale.max(1.0);
+            let radius = filter.support() * stretch;
+            let lo = libm::floorf(center - radius) as isize;
+            let hi = libm::ceilf(center + radius) as isize;
+
+            let first = lo.max(0).min(last as isize) as usize;
+            let end = (hi.max(0) as usize).min(last) + 1;
+            let start = weights.len();
+            weights.resize(start + (end - first), 0.0);
+
+            let mut sum = 0.0;
+            for j in lo..=hi {
+                let weight = filter.kernel((j as f32 + 0.5 - center) / stretch);
+                // Clamp to the edge, accumulating the weight of all outside pixels there.
+                let idx = j.max(0).min(last as isize) as usize;
+                weights[start + idx - first] += weight;
+                sum += weight;
+            }
+
+            if sum != 0.0 {
+                weights[start..].iter_mut().for_each(|w| *w /= sum);
+            }
+
+            taps.push((first, start, weights.len()));
+        }
+
+        Weights {
+            src_len,
+            taps,
+            weights,
+        }
+    }
+
+    fn dst_len(&self) -> usize {
+        self.taps.len()
+    }
+}
+
+impl Resampler {
+    /// Prepare resampling from an image of size `from` to one of size `to`.
+    ///
+    /// Both sizes are given as `(width, height)`.
+    ///
+    /// # Panics
+    ///
+    /// This panics if the source is empty in one dimension while the destination is not.
+    pub fn new(filter: Filter, from: (usize, usize), to: (usize, usize)) -> Self {
+        Resampler {
+            horizontal: Weights::new(filter, from.0, to.0),
+            vertical: Weights::new(filter, from.1, to.1),
+        }
+    }
+
+    /// Scale the source matrix into the destination.
+    ///
+    /// # Panics
+    ///
+    /// This panics if the dimensions of either matrix differ from those this resampler was
+    /// constructed for.
+    pub fn resample<P: Resample>(&self, src: &Matrix<P>, dst: &mut Matrix<P>) {
+        let (src_layout, dst_layout) = (src.layout(), dst.layout());
+        assert_eq!(
+            (src_layout.width(), src_layout.height()),
+            (self.horizontal.src_len, self.vertical.src_len),
+            "Mismatching source dimensions"
+        );
+        assert_eq!(
+            (dst_layout.width(), dst_layout.height()),
+            (self.horizontal.dst_len(), self.vertical.dst_len()),
+            "Mismatching destination dimensions"
+        );
+
+        self.resample_slices(src.as_slice(), dst.as_mut_slice(), None)
+    }
+
+    /// Scale a row-major slice of pixels into another, with the dimensions of this resampler.
+    ///
+    /// With a transfer function the color channels are filtered in linear light. These are all
+    /// channels except for the last one of pixels with two or four channels, which is alpha.
+    pub(crate) fn resample_slices<P: Resample>(
+        &self,
+        source: &[P],
+        target: &mut [P],
+        transfer: Option<&Linearize<P::Sample>>,
+    ) {
+        let channels = P::CHANNELS;
+        let src_width = self.horizontal.src_len;
+        let width = self.horizontal.dst_len();
+        let row_len = width * channels;
+        let colors = match channels {
+            2 | 4 => channels - 1,
+            _ => channels,
+        };
+
+        let store = |pixel: &mut [f32]| {
+            if let Some(transfer) = transfer {
+                for ch in &mut pixel[..colors] {
+                    *ch = transfer.encode(*ch);
+                }
+            }
+            P::store(pixel)
+        };
+
+        // Decode each source row once, then scale it horizontally.
+        let mut rows = alloc::vec![0.0f32; row_len * self.vertical.src_len];
+        let mut linear = alloc::vec![0.0f32; src_width * channels];
+        for (src_row, row) in source
+            .chunks_exact(src_width.max(1))
+            .zip(rows.chunks_exact_mut(row_len.max(1)))
+        {
+            for (value, pixel) in src_row.iter().zip(linear.chunks_exact_mut(channels)) {
+                for (idx, (ch, &sample)) in pixel.iter_mut().zip(value.samples()).enumerate() {
+                    *ch = match transfer {
+                        Some(transfer) if idx < colors => transfer.decode(sample),
+                        _ => sample.to_f32(),
+                    };
+                }
+            }
+
+            for (&(first, start, end), out) in self
+                .horizontal
+                .taps
+                .iter()
+                .zip(row.chunks_exact_mut(channels))
+            {
+                let weights = &self.horizontal.weights[start..end];
+                let pixels = linear[first * channels..].chunks_exact(channels);
+                for (&weight, pixel) in weights.iter().zip(pixels) {
+                    for (out, ch) in out.iter_mut().zip(pixel) {
+                        *out += weight * ch;
+                    }
+                }
+            }
+        }
+
+        // Then combine the scaled rows vertically.
+        let mut accum = alloc::vec![0.0f32; row_len];
+        for (&(first, start, end), dst_row) in self
+            .vertical
+            .taps
+            .iter()
+            .zip(target.chunks_exact_mut(width.max(1)))
+        {
+            accum.iter_mut().for_each(|v| *v = 0.0);
+            let weights = &self.vertical.weights[start..end];
+            for (idx, &weight) in weights.iter().enumerate() {
+                let row = &rows[(first + idx) * row_len..][..row_len];
+                for (acc, value) in accum.iter_mut().zip(row) {
+                    *acc += weight * value;
+                }
+            }
+
+            for (out, channels) in dst_row.iter_mut().zip(accum.chunks_exact_mut(channels)) {
+                *out = store(channels);
+            }
+        }
+    }
+}
+
+/// Scale the source matrix to the dimensions of the destination.
+///
+/// See [`Resampler`] to reuse the filter weights for multiple images.
+pub fn resample<P: Resample>(src: &Matrix<P>, dst: &mut Matrix<P>, filter: Filter) {
+    let (src_layout, dst_layout) = (src.layout(), dst.layout());
+    Resampler::new(
+        filter,
+        (src_layout.width(), src_layout.height()),
+        (dst_layout.width(), dst_layout.height()),
+    )
+    .resample(src, dst)
+}
+
+impl Sample for u8 {
+    fn to_f32(self) -> f32 {
+        f32::from(self)
+    }
+
+    fn from_f32(val: f32) -> Self {
+        // Casts saturate, including negative values.
+        (val + 0.5) as u8
+    }
+}
+
+impl Sample for u16 {
+    fn to_f32(self) -> f32 {
+        f32::from(self)
+    }
+
+    fn from_f32(val: f32) -> Self {
+        // Casts saturate, including negative values.
+        (val + 0.5) as u16
+    }
+}
+
+impl Sample for f32 {
+    fn to_f32(self) -> f32 {
+        self
+    }
+
+    fn from_f32(val: f32) -> Self {
+        val
+    }
+}
+
+impl<S: Sample> Resample for S {
+    type Sample = S;
+    const CHANNELS: usize = 1;
+
+    fn samples(&self) -> &[S] {
+        core::slice::from_ref(self)
+    }
+
+    fn store(from: &[f32]) -> Self {
+        S::from_f32(from[0])
+    }
+}
+
+impl<S: Sample, const N: usize> Resample for [S; N] {
+    type Sample = S;
+    const CHANNELS: usize = N;
+
+    fn samples(&self) -> &[S] {
+        self
+    }
+
+    fn store(from: &[f32]) -> Self {
+        core::array::from_fn(|idx| S::from_f32(from[idx]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pixels::{F32, U16, U8};
+    use crate::{Layout, Pixel};
+
+    fn empty<P>(pixel: Pixel<P>, width: usize, height: usize) -> Matrix<P> {
+        Matrix::with_layout(Layout::width_and_height_for_pixel(pixel, width, height).unwrap())
+    }
+
+    fn matrix<P: Copy>(pixel: Pixel<P>, width: usize, height: usize, data: &[P]) -> Matrix<P> {
+        let mut matrix = empty(pixel, width, height);
+        matrix.as_mut_slice().copy_from_slice(data);
+        matrix
+    }
+
+    #[test]
+    fn identity() {
+        let data: Vec<u8> = (0..12).map(|x| x * 20).collect();
+        let src = matrix(U8, 4, 3, &data);
+
+        for &filter in &[
+            Filter::Nearest,
+            Filter::Bilinear,
+            Filter::Bicubic,
+            Filter::Lanczos3,
+            Filter::Box,
+            Filter::Kaiser,
+        ] {
+            let mut dst = empty(U8, 4, 3);
+            resample(&src, &mut dst, filter);
+            assert_eq!(dst.as_slice(), src.as_slice(), "{:?}", filter);
+        }
+    }
+
+    #[test]
+    fn constant_stays_constant() {
+        let src = matrix(U16.array4(), 5, 3, &[[10, 200, 3000, 65535]; 15]);
+
+        for &filter in &[
+            Filter::Bilinear,
+            Filter::Bicubic,
+            Filter::Lanczos3,
+            Filter::Box,
+            Filter::Kaiser,
+        ] {
+            let mut up = empty(U16.array4(), 13, 7);
+            resample(&src, &mut up, filter);
+            assert!(up.as_slice().iter().all(|&p| p == [10, 200, 3000, 65535]));
+
+            let mut down = empty(U16.array4(), 2, 1);
+            resample(&src, &mut down, filter);
+            assert!(down.as_slice().iter().all(|&p| p == [10, 200, 3000, 65535]));
+        }
+    }
+
+    #[test]
+    fn nearest_upscale() {
+        let src = matrix(F32.array2(), 2, 1, &[[0.0, 1.0], [2.0, 3.0]]);
+        let mut dst = empty(F32.array2(), 4, 2);
+        resample(&src, &mut dst, Filter::Nearest);
+        assert_eq!(
+            dst.as_slice(),
+            &[[0.0, 1.0], [0.0, 1.0], [2.0, 3.0], [2.0, 3.0]].repeat(2)[..]
+        );
+    }
+
+    #[test]
+    fn bilinear_downscale() {
+        let src = matrix(U8, 4, 1, &[0, 100, 200, 250]);
+        let mut dst = empty(U8, 2, 1);
+        resample(&src, &mut dst, Filter::Bilinear);
+        // Triangle weights of 1:3:3:1, where the weight outside the image goes to its edge.
+        assert_eq!(dst.as_slice(), &[63, 213]);
+    }
+
+    #[test]
+    fn linear_tables() {
+        let function = TransferFunction::Srgb;
+        let table = Linearize::<u16>::new(function);
+        let direct = |v: u16| function.to_linear(f32::from(v) / 65535.0) * 65535.0;
+        for value in (0..=u16::MAX).step_by(1000) {
+            assert_eq!(table.decode(value), direct(value));
+        }
+
+        let floats = Linearize::<f32>::new(function);
+        assert_eq!(floats.decode(0.5), function.to_linear(0.5));
+        assert!((floats.encode(floats.decode(0.5)) - 0.5).abs() < 1e-6);
+    }
+}
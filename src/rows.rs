@@ -0,0 +1,358 @@
+//! Access to the rows of a matrix, and splitting it into disjoint bands of rows.
+use core::mem;
+use core::ops::{Index, IndexMut};
+
+use crate::Matrix;
+
+/// An iterator over the rows of a matrix or band, from top to bottom.
+#[derive(Clone, Debug)]
+pub struct Rows<'data, P> {
+    rest: &'data [P],
+    width: usize,
+    rows: usize,
+}
+
+/// An iterator over the mutable rows of a matrix or band, from top to bottom.
+#[derive(Debug)]
+pub struct RowsMut<'data, P> {
+    rest: &'data mut [P],
+    width: usize,
+    rows: usize,
+}
+
+/// A mutable view of consecutive, full rows of a matrix.
+///
+/// Bands are created by [`Matrix::split_rows_mut`] and [`Matrix::chunks_rows_mut`]. They do not
+/// overlap so that each can be sent to a different thread.
+#[derive(Debug)]
+pub struct BandMut<'data, P> {
+    data: &'data mut [P],
+    width: usize,
+    height: usize,
+    top: usize,
+}
+
+/// An iterator over disjoint bands of the rows of a matrix, from top to bottom.
+#[derive(Debug)]
+pub struct BandsMut<'data, P> {
+    rest: &'data mut [P],
+    width: usize,
+    top: usize,
+    rows: usize,
+    split: Split,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Split {
+    /// Split the remaining rows into this many bands of almost equal height.
+    Into(usize),
+    /// Split into bands of this many rows, the last one may be shorter.
+    Chunks(usize),
+}
+
+/// Row access.
+impl<P> Matrix<P> {
+    /// Get a row of pixels.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `y` is not smaller than the height of the matrix.
+    pub fn row(&self, y: usize) -> &[P] {
+        let width = self.layout().width();
+        assert!(y < self.layout().height(), "Coordinate out of bounds.");
+        &self.as_slice()[y * width..][..width]
+    }
+
+    /// Get a row of pixels mutably.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `y` is not smaller than the height of the matrix.
+    pub fn row_mut(&mut self, y: usize) -> &mut [P] {
+        let width = self.layout().width();
+        assert!(y < self.layout().height(), "Coordinate out of bounds.");
+        &mut self.as_mut_slice()[y * width..][..width]
+    }
+
+    /// Iterate over all rows, from top to bottom.
+    pub fn rows(&self) -> Rows<'_, P> {
+        let layout = self.layout();
+        Rows {
+            rest: self.as_slice(),
+            width: layout.width(),
+            rows: layout.height(),
+        }
+    }
+
+    /// Iterate mutably over all rows, from top to bottom.
+    pub fn rows_mut(&mut self) -> RowsMut<'_, P> {
+        let layout = self.layout();
+        RowsMut {
+            rest: self.as_mut_slice(),
+            width: layout.width(),
+            rows: layout.height(),
+        }
+    }
+
+    /// Split the rows into `n` bands of almost equal height.
+    ///
+    /// The heights of the bands differ by at most one, with the larger bands first. Some bands
+    /// are empty if there are fewer rows than bands.
+    ///
+    /// ```
+    /// use canvas::Matrix;
+    ///
+    /// let mut matrix = Matrix::<u8>::with_width_and_height(4, 5);
+    /// std::thread::scope(|scope| {
+    ///     for mut band in matrix.split_rows_mut(2) {
+    ///         scope.spawn(move || {
+    ///             let top = band.top() as u8;
+    ///             band.as_mut_slice().iter_mut().for_each(|p| *p = top);
+    ///         });
+    ///     }
+    /// });
+    ///
+    /// assert_eq!(matrix.row(2), &[0; 4]);
+    /// assert_eq!(matrix.row(3), &[3; 4]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `n` is zero.
+    pub fn split_rows_mut(&mut self, n: usize) -> BandsMut<'_, P> {
+        assert!(n != 0, "Band count must not be zero");
+        self.bands_mut(Split::Into(n))
+    }
+
+    /// Split the rows into bands of `k` rows each, except for a possibly shorter last band.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `k` is zero.
+    pub fn chunks_rows_mut(&mut self, k: usize) -> BandsMut<'_, P> {
+        assert!(k != 0, "Band height must not be zero");
+        self.bands_mut(Split::Chunks(k))
+    }
+
+    fn bands_mut(&mut self, split: Split) -> BandsMut<'_, P> {
+        let layout = self.layout();
+        BandsMut {
+            rest: self.as_mut_slice(),
+            width: layout.width(),
+            top: 0,
+            rows: layout.height(),
+            split,
+        }
+    }
+}
+
+impl<'data, P> BandMut<'data, P> {
+    /// The number of pixels in each row.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The number of rows.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The index of the first row within the matrix.
+    pub fn top(&self) -> usize {
+        self.top
+    }
+
+    /// Get all pixels, row by row.
+    pub fn as_slice(&self) -> &[P] {
+        self.data
+    }
+
+    /// Get all pixels mutably, row by row.
+    pub fn as_mut_slice(&mut self) -> &mut [P] {
+        self.data
+    }
+
+    /// Convert into a mutable slice of all pixels with the lifetime of the matrix.
+    pub fn into_mut_slice(self) -> &'data mut [P] {
+        self.data
+    }
+
+    /// Get a row of pixels, relative to the top of the band.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `y` is not smaller than the height of the band.
+    pub fn row(&self, y: usize) -> &[P] {
+        assert!(y < self.height, "Coordinate out of bounds.");
+        &self.data[y * self.width..][..self.width]
+    }
+
+    /// Get a row of pixels mutably, relative to the top of the band.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `y` is not smaller than the height of the band.
+    pub fn row_mut(&mut self, y: usize) -> &mut [P] {
+        assert!(y < self.height, "Coordinate out of bounds.");
+        &mut self.data[y * self.width..][..self.width]
+    }
+
+    /// Iterate over all rows, from top to bottom.
+    pub fn rows(&self) -> Rows<'_, P> {
+        Rows {
+            rest: self.data,
+            width: self.width,
+            rows: self.height,
+        }
+    }
+
+    /// Iterate mutably over all rows, from top to bottom.
+    pub fn rows_mut(&mut self) -> RowsMut<'_, P> {
+        RowsMut {
+            rest: self.data,
+            width: self.width,
+            rows: self.height,
+        }
+    }
+
+    fn index_of(&self, x: usize, y: usize) -> usize {
+        assert!(
+            x < self.width && y < self.height,
+            "Coordinate out of bounds."
+        );
+        y * self.width + x
+    }
+}
+
+impl<'data, P> Iterator for Rows<'data, P> {
+    type Item = &'data [P];
+
+    fn next(&mut self) -> Option<&'data [P]> {
+        self.rows = self.rows.checked_sub(1)?;
+        let (row, rest) = self.rest.split_at(self.width);
+        self.rest = rest;
+        Some(row)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.rows, Some(self.rows))
+    }
+}
+
+impl<P> ExactSizeIterator for Rows<'_, P> {}
+
+impl<'data, P> Iterator for RowsMut<'data, P> {
+    type Item = &'data mut [P];
+
+    fn next(&mut self) -> Option<&'data mut [P]> {
+        self.rows = self.rows.checked_sub(1)?;
+        let (row, rest) = mem::take(&mut self.rest).split_at_mut(self.width);
+        self.rest = rest;
+        Some(row)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.rows, Some(self.rows))
+    }
+}
+
+impl<P> ExactSizeIterator for RowsMut<'_, P> {}
+
+impl<'data, P> Iterator for BandsMut<'data, P> {
+    type Item = BandMut<'data, P>;
+
+    fn next(&mut self) -> Option<BandMut<'data, P>> {
+        let height = match &mut self.split {
+            Split::Into(bands) => {
+                let count = *bands;
+                *bands = count.checked_sub(1)?;
+                self.rows.div_ceil(count)
+            }
+            Split::Chunks(_) if self.rows == 0 => return None,
+            Split::Chunks(rows) => self.rows.min(*rows),
+        };
+
+        let (data, rest) = mem::take(&mut self.rest).split_at_mut(height * self.width);
+        let band = BandMut {
+            data,
+            width: self.width,
+            height,
+            top: self.top,
+        };
+
+        self.rest = rest;
+        self.top += height;
+        self.rows -= height;
+        Some(band)
+    }
+}
+
+impl<P> Index<(usize, usize)> for BandMut<'_, P> {
+    type Output = P;
+
+    fn index(&self, (x, y): (usize, usize)) -> &P {
+        &self.data[self.index_of(x, y)]
+    }
+}
+
+impl<P> IndexMut<(usize, usize)> for BandMut<'_, P> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut P {
+        let index = self.index_of(x, y);
+        &mut self.data[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn rows() {
+        let mut matrix = Matrix::<u16>::with_width_and_height(3, 2);
+        matrix.row_mut(1).copy_from_slice(&[1, 2, 3]);
+        assert_eq!(matrix.row(1), &[1, 2, 3]);
+        assert_eq!(matrix[(2, 1)], 3);
+
+        let rows = matrix.rows().collect::<Vec<_>>();
+        assert_eq!(rows, [&[0, 0, 0], &[1, 2, 3]]);
+
+        for (y, row) in matrix.rows_mut().enumerate() {
+            row[0] = 10 + y as u16;
+        }
+        assert_eq!(matrix.row(0), &[10, 0, 0]);
+
+        let mut empty = Matrix::<u16>::with_width_and_height(0, 3);
+        assert_eq!(empty.rows().len(), 3);
+        assert!(empty.rows_mut().all(|row| row.is_empty()));
+    }
+
+    #[test]
+    fn bands() {
+        let mut matrix = Matrix::<u8>::with_width_and_height(2, 7);
+        let heights = |bands: BandsMut<'_, u8>| {
+            bands
+                .map(|band| (band.top(), band.height()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(heights(matrix.split_rows_mut(3)), [(0, 3), (3, 2), (5, 2)]);
+        assert_eq!(
+            heights(matrix.split_rows_mut(9)).len(),
+            9,
+            "Empty bands are yielded"
+        );
+        assert_eq!(heights(matrix.chunks_rows_mut(3)), [(0, 3), (3, 3), (6, 1)]);
+
+        for mut band in matrix.chunks_rows_mut(2) {
+            let top = band.top() as u8;
+            band[(1, 0)] = top;
+            if band.height() > 1 {
+                band.row_mut(1)[0] = top + 1;
+            }
+        }
+
+        let expected = [0, 0, 1, 0, 0, 2, 3, 0, 0, 4, 5, 0, 0, 6];
+        assert_eq!(matrix.as_slice(), &expected);
+    }
+}
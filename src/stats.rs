@@ -0,0 +1,325 @@
+//! Statistics and histograms of the channels of an image.
+//!
+//! The samples are interpreted in their natural range as in the [`metrics`](crate::metrics)
+//! module. Histograms divide the range from zero to the peak value of the sample type into bins of
+//! equal width. With the default of 256 bins each bin of an 8-bit channel contains exactly one
+//! value.
+//!
+//! ```
+//! use canvas::Matrix;
+//! use canvas::layout::Rect;
+//! use canvas::stats::Analyzer;
+//!
+//! let mut matrix = Matrix::<[u8; 2]>::with_width_and_height(4, 4);
+//! matrix[(1, 1)] = [200, 255];
+//! matrix[(2, 1)] = [100, 255];
+//!
+//! let rect = Rect { x: 1, y: 1, width: 2, height: 1 };
+//! let stats = Analyzer::new().with_rect(rect).statistics(&matrix);
+//! assert_eq!(stats[0].mean, 150.0);
+//! assert_eq!(stats[0].variance, 2500.0);
+//!
+//! let histograms = Analyzer::new().histograms(&matrix);
+//! assert_eq!(histograms[0].bins()[0], 14);
+//! assert_eq!(histograms[1].bins()[255], 2);
+//! ```
+use alloc::vec::Vec;
+
+use crate::layout::Rect;
+use crate::metrics::{Sample, Samples};
+use crate::pixel::AsPixel;
+use crate::stride::ByteCanvasRef;
+use crate::Matrix;
+
+/// Selects the pixels to analyze and the resolution of histograms.
+#[derive(Clone, Copy, Debug)]
+pub struct Analyzer<'mask> {
+    bins: usize,
+    rect: Option<Rect>,
+    mask: Option<&'mask Matrix<u8>>,
+}
+
+/// The statistics of the samples of one channel.
+///
+/// All values are zero if no pixel was analyzed.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Statistics {
+    /// The number of analyzed samples.
+    pub count: usize,
+    /// The smallest sample.
+    pub min: f64,
+    /// The largest sample.
+    pub max: f64,
+    /// The arithmetic mean of the samples.
+    pub mean: f64,
+    /// The population variance of the samples.
+    pub variance: f64,
+}
+
+/// The distribution of the samples of one channel.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Histogram {
+    bins: Vec<u64>,
+}
+
+impl<'mask> Analyzer<'mask> {
+    /// Analyze all pixels, with 256 histogram bins.
+    pub fn new() -> Self {
+        Analyzer {
+            bins: 256,
+            rect: None,
+            mask: None,
+        }
+    }
+
+    /// Change the number of histogram bins, at least one.
+    pub fn with_bins(self, bins: usize) -> Self {
+        Analyzer {
+            bins: bins.max(1),
+            ..self
+        }
+    }
+
+    /// Only analyze the pixels within a rectangle.
+    ///
+    /// The rectangle is clipped to the image, pixels outside of it are ignored.
+    pub fn with_rect(self, rect: Rect) -> Self {
+        Analyzer {
+            rect: Some(rect),
+            ..self
+        }
+    }
+
+    /// Only analyze the pixels where a mask, such as the alpha channel, is not zero.
+    ///
+    /// The mask must have the dimensions of the analyzed images.
+    pub fn with_mask(self, mask: &'mask Matrix<u8>) -> Self {
+        Analyzer {
+            mask: Some(mask),
+            ..self
+        }
+    }
+
+    /// Compute the statistics of each channel.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if a mask does not have the dimensions of the matrix.
+    pub fn statistics<P: Samples>(&self, matrix: &Matrix<P>) -> Vec<Statistics> {
+        // Welford's algorithm, accumulating the sum of squared differences from the mean.
+        let mut stats = Vec::new();
+        stats.resize(P::CHANNELS, (Statistics::default(), 0.0f64));
+
+        self.visit(matrix, |pixel| {
+            for ((stat, squares), &sample) in stats.iter_mut().zip(pixel.samples()) {
+                let value = sample.to_f64();
+                if stat.count == 0 {
+                    stat.min = value;
+                    stat.max = value;
+                } else {
+                    stat.min = stat.min.min(value);
+                    stat.max = stat.max.max(value);
+                }
+
+                stat.count += 1;
+                let delta = value - stat.mean;
+                stat.mean += delta / stat.count as f64;
+                *squares += delta * (value - stat.mean);
+            }
+        });
+
+        stats
+            .into_iter()
+            .map(|(stat, squares)| Statistics {
+                variance: if stat.count == 0 {
+                    0.0
+                } else {
+                    squares / stat.count as f64
+                },
+                ..stat
+            })
+            .collect()
+    }
+
+    /// Count the samples of each channel in bins.
+    ///
+    /// Samples below zero or above the peak value are counted in the first or last bin. Samples
+    /// that are not a number are not counted.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if a mask does not have the dimensions of the matrix.
+    pub fn histograms<P: Samples>(&self, matrix: &Matrix<P>) -> Vec<Histogram> {
+        let mut histograms = Vec::new();
+        histograms.resize(
+            P::CHANNELS,
+            Histogram {
+                bins: alloc::vec![0; self.bins],
+            },
+        );
+
+        let scale = self.bins as f64 / <P::Sample as Sample>::PEAK;
+        let last = self.bins - 1;
+        self.visit(matrix, |pixel| {
+            for (histogram, &sample) in histograms.iter_mut().zip(pixel.samples()) {
+                let value = sample.to_f64();
+                if value.is_nan() {
+                    continue;
+                }
+
+                // Saturating conversion, negative values go to the first bin.
+                let bin = ((value * scale) as usize).min(last);
+                histogram.bins[bin] += 1;
+            }
+        });
+
+        histograms
+    }
+
+    /// Compute the statistics of each channel of a strided canvas, such as a DRM plane.
+    ///
+    /// Returns `None` if the element size of the canvas is not the size of `P`.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if a mask does not have the dimensions of the canvas.
+    pub fn canvas_statistics<P: Samples + AsPixel>(
+        &self,
+        canvas: ByteCanvasRef<'_>,
+    ) -> Option<Vec<Statistics>> {
+        Some(self.statistics(&Self::typed::<P>(canvas)?))
+    }
+
+    /// Count the samples of each channel of a strided canvas in bins.
+    ///
+    /// Returns `None` if the element size of the canvas is not the size of `P`.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if a mask does not have the dimensions of the canvas.
+    pub fn canvas_histograms<P: Samples + AsPixel>(
+        &self,
+        canvas: ByteCanvasRef<'_>,
+    ) -> Option<Vec<Histogram>> {
+        Some(self.histograms(&Self::typed::<P>(canvas)?))
+    }
+
+    fn typed<P: AsPixel>(canvas: ByteCanvasRef<'_>) -> Option<Matrix<P>> {
+        if canvas.layout().spec().element.size() != P::pixel().size() {
+            return None;
+        }
+
+        Some(canvas.to_matrix::<P>())
+    }
+
+    /// Call a function with each selected pixel, row by row.
+    fn visit<P>(&self, matrix: &Matrix<P>, mut f: impl FnMut(&P)) {
+        let layout = matrix.layout();
+        let (width, height) = (layout.width(), layout.height());
+
+        if let Some(mask) = self.mask {
+            assert!(
+                (mask.layout().width(), mask.layout().height()) == (width, height),
+                "Mismatching dimensions."
+            );
+        }
+
+        let full = Rect {
+            x: 0,
+            y: 0,
+            width: u32::MAX,
+            height: u32::MAX,
+        };
+        let rect = self.rect.unwrap_or(full);
+        let x = (rect.x as usize).min(width);
+        let y = (rect.y as usize).min(height);
+        let w = (rect.width as usize).min(width - x);
+        let h = (rect.height as usize).min(height - y);
+
+        for row in y..y + h {
+            for col in x..x + w {
+                if self.mask.is_none_or(|mask| mask[(col, row)] != 0) {
+                    f(&matrix[(col, row)]);
+                }
+            }
+        }
+    }
+}
+
+impl Histogram {
+    /// The number of samples in each bin.
+    pub fn bins(&self) -> &[u64] {
+        &self.bins
+    }
+
+    /// The number of counted samples.
+    pub fn total(&self) -> u64 {
+        self.bins.iter().sum()
+    }
+}
+
+impl Default for Analyzer<'_> {
+    fn default() -> Self {
+        Analyzer::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statistics() {
+        let mut matrix = Matrix::<u16>::with_width_and_height(3, 2);
+        matrix.as_mut_slice().copy_from_slice(&[2, 4, 4, 4, 5, 5]);
+        let stats = Analyzer::new().statistics(&matrix);
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].count, 6);
+        assert_eq!((stats[0].min, stats[0].max), (2.0, 5.0));
+        assert_eq!(stats[0].mean, 4.0);
+        assert!((stats[0].variance - 1.0).abs() < 1e-12);
+
+        let mut alpha = Matrix::<u8>::with_width_and_height(3, 2);
+        alpha[(0, 0)] = 255;
+        alpha[(2, 1)] = 1;
+        let masked = Analyzer::new().with_mask(&alpha).statistics(&matrix);
+        assert_eq!(masked[0].count, 2);
+        assert_eq!(masked[0].mean, 3.5);
+
+        let empty = Rect {
+            x: 3,
+            y: 0,
+            width: 5,
+            height: 5,
+        };
+        let none = Analyzer::new().with_rect(empty).statistics(&matrix);
+        assert_eq!(none[0], Statistics::default());
+    }
+
+    #[test]
+    fn histograms() {
+        let mut matrix = Matrix::<[f32; 2]>::with_width_and_height(2, 2);
+        matrix.as_mut_slice().copy_from_slice(&[
+            [0.0, -1.0],
+            [0.3, 0.5],
+            [1.0, 2.0],
+            [0.99, f32::NAN],
+        ]);
+
+        let histograms = Analyzer::new().with_bins(4).histograms(&matrix);
+        assert_eq!(histograms[0].bins(), &[1, 1, 0, 2]);
+        assert_eq!(histograms[1].bins(), &[1, 0, 1, 1]);
+        assert_eq!(histograms[1].total(), 3);
+
+        let mut wide = Matrix::<u16>::with_width_and_height(2, 1);
+        wide.as_mut_slice().copy_from_slice(&[1024, 65535]);
+        let histograms = Analyzer::new().with_bins(64).histograms(&wide);
+        assert_eq!(histograms[0].bins()[1], 1);
+        assert_eq!(histograms[0].bins()[63], 1);
+
+        let canvas = ByteCanvasRef::from(&wide).flipped_horizontally();
+        let stats = Analyzer::new().canvas_statistics::<u16>(canvas.as_ref());
+        assert_eq!(stats.unwrap()[0].min, 1024.0);
+        assert!(Analyzer::new().canvas_histograms::<u8>(canvas).is_none());
+    }
+}
@@ -14,9 +14,11 @@
 //! canvas. They internally contain a simple byte slice which allows viewing any source buffer as a
 //! strided matrix even when it was not allocated with the special allocator.
 use crate::canvas::Canvas;
-use crate::layout::Layout;
+use crate::layout::{Coord, Layout, Rect};
 use crate::pixel::AsPixel;
-use crate::{layout, matrix};
+use crate::{layout, matrix, Matrix, Pixel};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
 use core::ops::Range;
 
 /// A simple layout describing some pixels as a byte matrix.
@@ -32,10 +34,17 @@ pub struct StrideSpec {
     /// individual pixels. Otherwise, whole rows or columns of contiguous data may be inspected.
     pub element: layout::Element,
     /// The number of bytes to go one pixel along the width.
-    pub width_stride: usize,
+    ///
+    /// This may be negative, for example to describe a horizontally mirrored image.
+    pub width_stride: isize,
     /// The number of bytes to go one pixel along the height.
-    pub height_stride: usize,
-    /// Offset of this matrix from the start.
+    ///
+    /// This may be negative, for example to describe a bottom-up image such as a BMP.
+    pub height_stride: isize,
+    /// Offset of the first element, at coordinate `(0, 0)`, from the start.
+    ///
+    /// With negative strides other elements may come before this one in memory but no element
+    /// may start before the beginning of the buffer.
     pub offset: usize,
 }
 
@@ -69,6 +78,8 @@ enum BadStrideKind {
     UnalignedOffset,
     UnalignedWidthStride,
     UnalignedHeightStride,
+    UnalignedDepthStride,
+    BeforeStart,
     OutOfMemory,
 }
 
@@ -82,6 +93,42 @@ pub struct ByteCanvasMut<'data> {
     data: &'data mut [u8],
 }
 
+/// A layout describing a stack of equally shaped strided matrices, such as the layers of an array
+/// texture or the slices of a volume.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct VolumeSpec {
+    /// The layout of the first slice, at depth zero.
+    pub slice: StrideSpec,
+    /// The number of slices.
+    pub depth: usize,
+    /// The number of bytes to go one slice along the depth.
+    ///
+    /// This may be negative, the offset of the first slice must then leave room for all others.
+    pub depth_stride: isize,
+}
+
+/// A validated layout of a stack of strided matrices, treated as bytes.
+///
+/// The invariants are those of `StrideLayout`, upheld for every slice, and that the whole volume
+/// fits into memory.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct VolumeLayout {
+    spec: VolumeSpec,
+    total: usize,
+}
+
+/// An untyped volume of pixels treated as pure bytes.
+pub struct ByteVolumeRef<'data> {
+    layout: VolumeLayout,
+    data: &'data [u8],
+}
+
+/// An untyped, mutable volume of pixels treated as pure bytes.
+pub struct ByteVolumeMut<'data> {
+    layout: VolumeLayout,
+    data: &'data mut [u8],
+}
+
 impl StrideSpec {
     /// Compare sizes without taking into account the offset or strides.
     fn matches(&self, other: &Self) -> bool {
@@ -90,57 +137,111 @@ impl StrideSpec {
             && self.height == other.height
     }
 
+    /// Whether each row is a contiguous byte range, in either direction.
+    ///
+    /// Empty layouts have no rows within the buffer, even if their strides are contiguous.
     fn has_contiguous_rows(&self) -> bool {
-        self.element.size() == self.width_stride
+        !self.is_empty() && self.element.size() as isize == self.width_stride.abs()
     }
 
+    /// Whether each column is a contiguous byte range, in either direction.
     fn has_contiguous_cols(&self) -> bool {
-        self.element.size() == self.height_stride
+        !self.is_empty() && self.element.size() as isize == self.height_stride.abs()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
     }
 
-    fn element_start(&self, row: usize, col: usize) -> usize {
-        (row * self.height_stride) + (col * self.width_stride) + self.offset
+    fn element_start(&self, x: usize, y: usize) -> usize {
+        // Can't overflow and is never negative, this was validated in `StrideLayout::new`.
+        let start = (x as isize) * self.width_stride
+            + (y as isize) * self.height_stride
+            + self.offset as isize;
+        start as usize
     }
 
-    fn element(&self, row: usize, col: usize) -> Range<usize> {
-        let start = self.element_start(row, col);
+    fn element(&self, x: usize, y: usize) -> Range<usize> {
+        let start = self.element_start(x, y);
         start..start + self.element.size()
     }
 
+    /// The elements of a rectangle within this layout, which must be in bounds.
+    fn sub_rect(&self, x: usize, y: usize, width: usize, height: usize) -> StrideSpec {
+        StrideSpec {
+            width,
+            height,
+            offset: self.element_start(x, y),
+            ..*self
+        }
+    }
+
     fn contiguous_row(&self, row: usize) -> Range<usize> {
-        let start = self.element_start(row, 0);
+        let start = match self.width.checked_sub(1) {
+            None => self.element_start(0, row),
+            // With a negative stride the row starts at its last element.
+            Some(last) => self
+                .element_start(0, row)
+                .min(self.element_start(last, row)),
+        };
         let length = self.width * self.element.size();
         start..start + length
     }
 
     fn contiguous_col(&self, col: usize) -> Range<usize> {
-        let start = self.element_start(0, col);
+        let start = match self.height.checked_sub(1) {
+            None => self.element_start(col, 0),
+            // With a negative stride the column starts at its last element.
+            Some(last) => self
+                .element_start(col, 0)
+                .min(self.element_start(col, last)),
+        };
         let length = self.height * self.element.size();
         start..start + length
     }
 
-    fn end(&self) -> Option<usize> {
+    /// The relative offsets of the lowest and highest element from the first element.
+    fn relative_extent(&self) -> Option<(isize, isize)> {
         if self.height == 0 || self.width == 0 {
-            return Some(self.offset);
+            return Some((0, 0));
         }
 
-        let max_w = self.width - 1;
-        let max_h = self.height - 1;
+        let max_w = isize::try_from(self.width - 1).ok()?;
+        let max_h = isize::try_from(self.height - 1).ok()?;
 
         let max_w_offset = max_w.checked_mul(self.width_stride)?;
         let max_h_offset = max_h.checked_mul(self.height_stride)?;
 
-        let relative_past_end = self
-            .element
-            .size()
-            .checked_add(max_h_offset)?
-            .checked_add(max_w_offset)?;
+        let low = max_w_offset.min(0).checked_add(max_h_offset.min(0))?;
+        let high = max_w_offset.max(0).checked_add(max_h_offset.max(0))?;
+        Some((low, high))
+    }
+
+    /// The first byte of any element.
+    ///
+    /// Returns `None` if negative strides would reach before the start of the buffer.
+    fn start(&self) -> Option<usize> {
+        let (low, _) = self.relative_extent()?;
+        let offset = isize::try_from(self.offset).ok()?;
+        usize::try_from(offset.checked_add(low)?).ok()
+    }
+
+    fn end(&self) -> Option<usize> {
+        if self.height == 0 || self.width == 0 {
+            return Some(self.offset);
+        }
+
+        let (_, high) = self.relative_extent()?;
+        let size = isize::try_from(self.element.size()).ok()?;
+        let relative_past_end = high.checked_add(size)?;
 
         // We wouldn't need to validated if there are no elements. However, this is basically the
         // caller's responsibility. It's more consistent if we keep the offset. For future
         // additions such as calculating free space (?) this would also be required.
-        let total = relative_past_end.checked_add(self.offset)?;
-        Some(total)
+        let total = isize::try_from(self.offset)
+            .ok()?
+            .checked_add(relative_past_end)?;
+        usize::try_from(total).ok()
     }
 }
 
@@ -148,21 +249,23 @@ impl StrideLayout {
     /// Try to create a new layout from a specification.
     ///
     /// This fails if the specification does not describe a valid layout. The reasons for this
-    /// include the element being misaligned according to the provided offsets/strides or the
-    /// layout not describing a memory size expressible on the current architecture.
+    /// include the element being misaligned according to the provided offsets/strides, negative
+    /// strides reaching before the start of the buffer or the layout not describing a memory size
+    /// expressible on the current architecture.
     pub fn new(spec: StrideSpec) -> Result<Self, BadStrideError> {
         if spec.offset % spec.element.align() != 0 {
             return Err(BadStrideKind::UnalignedOffset.into());
         }
 
-        if spec.width_stride % spec.element.align() != 0 {
+        if spec.width_stride % spec.element.align() as isize != 0 {
             return Err(BadStrideKind::UnalignedWidthStride.into());
         }
 
-        if spec.height_stride % spec.element.align() != 0 {
+        if spec.height_stride % spec.element.align() as isize != 0 {
             return Err(BadStrideKind::UnalignedHeightStride.into());
         }
 
+        spec.start().ok_or(BadStrideKind::BeforeStart)?;
         let total = spec.end().ok_or(BadStrideKind::OutOfMemory)?;
 
         Ok(StrideLayout { spec, total })
@@ -178,10 +281,10 @@ impl StrideLayout {
                 element: matrix.element(),
                 width: matrix.width(),
                 height: matrix.height(),
-                height_stride: matrix.element().size(),
+                height_stride: matrix.element().size() as isize,
                 // Overflow can't happen because all of `matrix` fits in memory according to its own
                 // internal invariant.
-                width_stride: matrix.height() * matrix.element().size(),
+                width_stride: (matrix.height() * matrix.element().size()) as isize,
                 offset: 0,
             },
             total: matrix.byte_len(),
@@ -200,8 +303,8 @@ impl StrideLayout {
                 height: matrix.height(),
                 // Overflow can't happen because all of `matrix` fits in memory according to its own
                 // internal invariant.
-                height_stride: matrix.width() * matrix.element().size(),
-                width_stride: matrix.element().size(),
+                height_stride: (matrix.width() * matrix.element().size()) as isize,
+                width_stride: matrix.element().size() as isize,
                 offset: 0,
             },
             total: matrix.byte_len(),
@@ -220,6 +323,71 @@ impl StrideLayout {
         self.spec.element = self.spec.element.infimum(new);
     }
 
+    /// Swap the roles of width and height.
+    ///
+    /// The pixel at `(x, y)` of the result is the pixel at `(y, x)` of the original. Like all the
+    /// following transformations this only changes the strides and offset; the result describes
+    /// exactly the same bytes and never requires a copy.
+    #[must_use = "This does not modify `self`."]
+    pub fn transposed(self) -> Self {
+        let spec = StrideSpec {
+            width: self.spec.height,
+            height: self.spec.width,
+            width_stride: self.spec.height_stride,
+            height_stride: self.spec.width_stride,
+            ..self.spec
+        };
+        StrideLayout { spec, ..self }
+    }
+
+    /// Mirror the matrix along its horizontal axis, i.e. reverse the order of rows.
+    #[must_use = "This does not modify `self`."]
+    pub fn flipped_vertically(self) -> Self {
+        if self.spec.width == 0 || self.spec.height <= 1 {
+            return self;
+        }
+
+        let spec = StrideSpec {
+            offset: self.spec.element_start(0, self.spec.height - 1),
+            height_stride: -self.spec.height_stride,
+            ..self.spec
+        };
+        StrideLayout { spec, ..self }
+    }
+
+    /// Mirror the matrix along its vertical axis, i.e. reverse the order of columns.
+    #[must_use = "This does not modify `self`."]
+    pub fn flipped_horizontally(self) -> Self {
+        if self.spec.height == 0 || self.spec.width <= 1 {
+            return self;
+        }
+
+        let spec = StrideSpec {
+            offset: self.spec.element_start(self.spec.width - 1, 0),
+            width_stride: -self.spec.width_stride,
+            ..self.spec
+        };
+        StrideLayout { spec, ..self }
+    }
+
+    /// Rotate the matrix by 90 degrees clockwise.
+    #[must_use = "This does not modify `self`."]
+    pub fn rotated_90(self) -> Self {
+        self.transposed().flipped_horizontally()
+    }
+
+    /// Rotate the matrix by 180 degrees.
+    #[must_use = "This does not modify `self`."]
+    pub fn rotated_180(self) -> Self {
+        self.flipped_horizontally().flipped_vertically()
+    }
+
+    /// Rotate the matrix by 270 degrees clockwise, or 90 degrees counter-clockwise.
+    #[must_use = "This does not modify `self`."]
+    pub fn rotated_270(self) -> Self {
+        self.transposed().flipped_vertically()
+    }
+
     fn matches(&self, other: &Self) -> bool {
         self.spec.matches(&other.spec)
     }
@@ -306,6 +474,14 @@ impl<'data> ByteCanvasRef<'data> {
         ByteCanvasRef { layout, data }
     }
 
+    /// View a byte slice as a strided canvas.
+    ///
+    /// Returns `None` if the slice is too short for the layout.
+    pub fn with_bytes(layout: StrideLayout, data: &'data [u8]) -> Option<Self> {
+        let data = data.get(..layout.total)?;
+        Some(ByteCanvasRef { layout, data })
+    }
+
     /// Shrink the element's size or alignment.
     ///
     /// This operation never reallocates the buffer.
@@ -313,6 +489,40 @@ impl<'data> ByteCanvasRef<'data> {
         self.layout.shrink_element(new)
     }
 
+    /// Get the layout of this matrix.
+    pub fn layout(&self) -> StrideLayout {
+        self.layout
+    }
+
+    /// The bytes of the element at a coordinate.
+    pub(crate) fn element_bytes(&self, x: usize, y: usize) -> &[u8] {
+        &self.data[self.layout.pixel(x, y)]
+    }
+
+    /// Pass the bytes of all elements, row by row, in order of increasing coordinates.
+    ///
+    /// Rows stored contiguously and in forward direction are passed as a whole.
+    pub(crate) fn visit_bytes(&self, mut visit: impl FnMut(&[u8])) {
+        let spec = &self.layout.spec;
+        if spec.element.size() as isize == spec.width_stride {
+            for row in 0..spec.height {
+                visit(&self.data[spec.contiguous_row(row)]);
+            }
+        } else {
+            for y in 0..spec.height {
+                for x in 0..spec.width {
+                    visit(self.element_bytes(x, y));
+                }
+            }
+        }
+    }
+
+    /// All bytes covered by the layout.
+    #[cfg_attr(not(feature = "ndarray"), allow(dead_code))]
+    pub(crate) fn data(&self) -> &'data [u8] {
+        self.data
+    }
+
     /// Borrow this as a reference to a strided byte matrix.
     pub fn as_ref(&self) -> ByteCanvasRef<'_> {
         ByteCanvasRef {
@@ -320,6 +530,115 @@ impl<'data> ByteCanvasRef<'data> {
             data: &*self.data,
         }
     }
+
+    /// View the transposed matrix, see [`StrideLayout::transposed`].
+    #[must_use = "This returns a new view of the same data."]
+    pub fn transposed(self) -> Self {
+        let layout = self.layout.transposed();
+        ByteCanvasRef { layout, ..self }
+    }
+
+    /// View the vertically mirrored matrix, see [`StrideLayout::flipped_vertically`].
+    #[must_use = "This returns a new view of the same data."]
+    pub fn flipped_vertically(self) -> Self {
+        let layout = self.layout.flipped_vertically();
+        ByteCanvasRef { layout, ..self }
+    }
+
+    /// View the horizontally mirrored matrix, see [`StrideLayout::flipped_horizontally`].
+    #[must_use = "This returns a new view of the same data."]
+    pub fn flipped_horizontally(self) -> Self {
+        let layout = self.layout.flipped_horizontally();
+        ByteCanvasRef { layout, ..self }
+    }
+
+    /// View the matrix rotated clockwise by 90 degrees, see [`StrideLayout::rotated_90`].
+    #[must_use = "This returns a new view of the same data."]
+    pub fn rotated_90(self) -> Self {
+        let layout = self.layout.rotated_90();
+        ByteCanvasRef { layout, ..self }
+    }
+
+    /// View the matrix rotated by 180 degrees, see [`StrideLayout::rotated_180`].
+    #[must_use = "This returns a new view of the same data."]
+    pub fn rotated_180(self) -> Self {
+        let layout = self.layout.rotated_180();
+        ByteCanvasRef { layout, ..self }
+    }
+
+    /// View the matrix rotated clockwise by 270 degrees, see [`StrideLayout::rotated_270`].
+    #[must_use = "This returns a new view of the same data."]
+    pub fn rotated_270(self) -> Self {
+        let layout = self.layout.rotated_270();
+        ByteCanvasRef { layout, ..self }
+    }
+
+    /// Copy all elements into a buffer with a tiled layout.
+    ///
+    /// The padding of partial tiles is not modified.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the dimensions or element sizes of the layouts differ, or if the
+    /// buffer is shorter than the tiled layout.
+    pub fn copy_to_tiled(&self, tiled: &layout::Tiled, bytes: &mut [u8]) {
+        let spec = self.layout.spec;
+        assert!(tiled_matches(&spec, tiled), "Mismatching layouts.");
+        assert!(
+            bytes.len() >= tiled.byte_len(),
+            "Buffer too short for the layout."
+        );
+
+        for_each_tile(tiled, |tile, x, y| {
+            let source = StrideLayout {
+                spec: spec.sub_rect(x, y, tile.spec.width, tile.spec.height),
+                total: self.layout.total,
+            };
+            ByteCanvasMut {
+                layout: tile,
+                data: &mut bytes[..tile.total],
+            }
+            .copy_from_canvas(ByteCanvasRef {
+                layout: source,
+                data: self.data,
+            });
+        });
+    }
+
+    /// Copy the pixels into a newly allocated, row-major matrix.
+    ///
+    /// See [`to_matrix_for_pixel`] for details.
+    ///
+    /// [`to_matrix_for_pixel`]: #method.to_matrix_for_pixel
+    pub fn to_matrix<P: AsPixel>(&self) -> Matrix<P> {
+        self.to_matrix_for_pixel(P::pixel())
+    }
+
+    /// Copy the pixels into a newly allocated, row-major matrix.
+    ///
+    /// This materializes any of the transformed views such as a transposed or rotated matrix.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the size of the pixel type differs from the element size of this
+    /// canvas or if the allocation fails.
+    pub fn to_matrix_for_pixel<P>(&self, pixel: Pixel<P>) -> Matrix<P> {
+        let spec = self.layout.spec;
+        assert_eq!(spec.element.size(), pixel.size(), "Mismatching pixel size.");
+        let layout = matrix::Layout::width_and_height_for_pixel(pixel, spec.width, spec.height)
+            .expect("Fits into memory because the canvas does");
+        let mut matrix = Matrix::with_layout(layout);
+
+        let target = layout::Matrix::from_width_height(spec.element, spec.width, spec.height)
+            .expect("Fits into memory because the canvas does");
+        ByteCanvasMut {
+            layout: StrideLayout::with_row_major(target),
+            data: matrix.as_bytes_mut(),
+        }
+        .copy_from_canvas(self.as_ref());
+
+        matrix
+    }
 }
 
 impl<'data> ByteCanvasMut<'data> {
@@ -330,6 +649,14 @@ impl<'data> ByteCanvasMut<'data> {
         ByteCanvasMut { layout, data }
     }
 
+    /// View a mutable byte slice as a strided canvas.
+    ///
+    /// Returns `None` if the slice is too short for the layout.
+    pub fn with_bytes(layout: StrideLayout, data: &'data mut [u8]) -> Option<Self> {
+        let data = data.get_mut(..layout.total)?;
+        Some(ByteCanvasMut { layout, data })
+    }
+
     /// Shrink the element's size or alignment.
     ///
     /// This operation never reallocates the buffer.
@@ -337,15 +664,36 @@ impl<'data> ByteCanvasMut<'data> {
         self.layout.shrink_element(new)
     }
 
+    /// Get the layout of this matrix.
+    pub fn layout(&self) -> StrideLayout {
+        self.layout
+    }
+
+    /// The bytes of the element at a coordinate.
+    pub(crate) fn element_bytes_mut(&mut self, x: usize, y: usize) -> &mut [u8] {
+        &mut self.data[self.layout.pixel(x, y)]
+    }
+
+    /// All bytes covered by the layout.
+    #[cfg_attr(not(feature = "ndarray"), allow(dead_code))]
+    pub(crate) fn data_mut(&mut self) -> &mut [u8] {
+        self.data
+    }
+
     /// Copy the bytes from another canvas.
     ///
     /// The source must have the same width, height, and element size.
     pub fn copy_from_canvas(&mut self, source: ByteCanvasRef<'_>) {
         assert!(self.layout.matches(&source.layout), "Mismatching layouts.");
         // FIXME: Special case copying for 100% contiguous layouts.
+        let (spec, src_spec) = (self.layout.spec, source.layout.spec);
 
-        if let Some(rows) = self.layout.contiguous_rows() {
-            if let Some(src_rows) = source.layout.contiguous_rows() {
+        // Contiguous rows can only be copied as a whole if they run in the same direction.
+        if spec.width_stride == src_spec.width_stride {
+            if let (Some(rows), Some(src_rows)) = (
+                self.layout.contiguous_rows(),
+                source.layout.contiguous_rows(),
+            ) {
                 for (row, src) in rows.zip(src_rows) {
                     self.data[row].copy_from_slice(&source.data[src]);
                 }
@@ -353,8 +701,11 @@ impl<'data> ByteCanvasMut<'data> {
             }
         }
 
-        if let Some(cols) = self.layout.contiguous_columns() {
-            if let Some(src_cols) = source.layout.contiguous_columns() {
+        if spec.height_stride == src_spec.height_stride {
+            if let (Some(cols), Some(src_cols)) = (
+                self.layout.contiguous_columns(),
+                source.layout.contiguous_columns(),
+            ) {
                 for (col, src) in cols.zip(src_cols) {
                     self.data[col].copy_from_slice(&source.data[src]);
                 }
@@ -362,13 +713,150 @@ impl<'data> ByteCanvasMut<'data> {
             }
         }
 
+        // Copy in small square blocks. If one matrix is transposed relative to the other then
+        // at least the accesses within a block stay close to each other in memory for both.
+        const BLOCK: usize = 16;
+        let (width, height) = (spec.width, spec.height);
+
         // Panics: we've validated that the widths and heights match.
-        for x in 0..self.layout.spec.width {
-            for y in 0..self.layout.spec.height {
-                let into = self.layout.pixel(x, y);
-                let from = source.layout.pixel(x, y);
-                // Panics: we've validated that the element sizes match.
-                self.data[into].copy_from_slice(&source.data[from]);
+        for y_block in (0..height).step_by(BLOCK) {
+            for x_block in (0..width).step_by(BLOCK) {
+                for y in y_block..height.min(y_block + BLOCK) {
+                    for x in x_block..width.min(x_block + BLOCK) {
+                        let into = self.layout.pixel(x, y);
+                        let from = source.layout.pixel(x, y);
+                        // Panics: we've validated that the element sizes match.
+                        self.data[into].copy_from_slice(&source.data[from]);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Copy all elements from a buffer with a tiled layout.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the dimensions or element sizes of the layouts differ, or if the
+    /// buffer is shorter than the tiled layout.
+    pub fn copy_from_tiled(&mut self, tiled: &layout::Tiled, bytes: &[u8]) {
+        let spec = self.layout.spec;
+        assert!(tiled_matches(&spec, tiled), "Mismatching layouts.");
+        assert!(
+            bytes.len() >= tiled.byte_len(),
+            "Buffer too short for the layout."
+        );
+
+        for_each_tile(tiled, |tile, x, y| {
+            let target = StrideLayout {
+                spec: spec.sub_rect(x, y, tile.spec.width, tile.spec.height),
+                total: self.layout.total,
+            };
+            ByteCanvasMut {
+                layout: target,
+                data: &mut *self.data,
+            }
+            .copy_from_canvas(ByteCanvasRef {
+                layout: tile,
+                data: &bytes[..tile.total],
+            });
+        });
+    }
+
+    /// Set all elements to the same bytes.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `element` does not have the size of an element.
+    pub fn fill(&mut self, element: &[u8]) {
+        self.fill_spec(self.layout.spec, element)
+    }
+
+    /// Set all elements within a rectangle to the same bytes.
+    ///
+    /// The rectangle is clipped to the canvas, elements outside of it are ignored.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `element` does not have the size of an element.
+    pub fn fill_rect(&mut self, rect: Rect, element: &[u8]) {
+        let spec = self.layout.spec;
+        let x = (rect.x as usize).min(spec.width);
+        let y = (rect.y as usize).min(spec.height);
+        let width = (rect.width as usize).min(spec.width - x);
+        let height = (rect.height as usize).min(spec.height - y);
+
+        if width == 0 || height == 0 {
+            assert_eq!(
+                element.len(),
+                spec.element.size(),
+                "Mismatching pixel size."
+            );
+            return;
+        }
+
+        // All elements of the rectangle are also elements of this canvas, so this is valid.
+        let rect = StrideSpec {
+            width,
+            height,
+            offset: spec.element_start(x, y),
+            ..spec
+        };
+
+        self.fill_spec(rect, element)
+    }
+
+    /// Set each element to the bytes computed from its coordinate.
+    ///
+    /// Elements are visited row-for-row.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the returned bytes do not have the size of an element, or if the
+    /// canvas has more rows or columns than can be represented by a `Coord`.
+    pub fn fill_with<B: AsRef<[u8]>>(&mut self, mut f: impl FnMut(Coord) -> B) {
+        let spec = self.layout.spec;
+        assert!(
+            u32::try_from(spec.width).is_ok() && u32::try_from(spec.height).is_ok(),
+            "Dimensions exceed coordinates."
+        );
+
+        for y in 0..spec.height {
+            for x in 0..spec.width {
+                let bytes = f(Coord(x as u32, y as u32));
+                let bytes = bytes.as_ref();
+                assert_eq!(bytes.len(), spec.element.size(), "Mismatching pixel size.");
+                self.data[spec.element(x, y)].copy_from_slice(bytes);
+            }
+        }
+    }
+
+    /// Fill a sub-matrix of the canvas.
+    fn fill_spec(&mut self, spec: StrideSpec, element: &[u8]) {
+        let size = spec.element.size();
+        assert_eq!(element.len(), size, "Mismatching pixel size.");
+
+        if size == 0 {
+            return;
+        }
+
+        if spec.has_contiguous_rows() {
+            for row in 0..spec.height {
+                self.data[spec.contiguous_row(row)]
+                    .chunks_exact_mut(size)
+                    .for_each(|bytes| bytes.copy_from_slice(element));
+            }
+        } else if spec.has_contiguous_cols() {
+            for col in 0..spec.width {
+                self.data[spec.contiguous_col(col)]
+                    .chunks_exact_mut(size)
+                    .for_each(|bytes| bytes.copy_from_slice(element));
+            }
+        } else {
+            for y in 0..spec.height {
+                for x in 0..spec.width {
+                    self.data[spec.element(x, y)].copy_from_slice(element);
+                }
             }
         }
     }
@@ -388,6 +876,306 @@ impl<'data> ByteCanvasMut<'data> {
             data: self.data,
         }
     }
+
+    /// View the transposed matrix, see [`StrideLayout::transposed`].
+    #[must_use = "This returns a new view of the same data."]
+    pub fn transposed(self) -> Self {
+        let layout = self.layout.transposed();
+        ByteCanvasMut { layout, ..self }
+    }
+
+    /// View the vertically mirrored matrix, see [`StrideLayout::flipped_vertically`].
+    #[must_use = "This returns a new view of the same data."]
+    pub fn flipped_vertically(self) -> Self {
+        let layout = self.layout.flipped_vertically();
+        ByteCanvasMut { layout, ..self }
+    }
+
+    /// View the horizontally mirrored matrix, see [`StrideLayout::flipped_horizontally`].
+    #[must_use = "This returns a new view of the same data."]
+    pub fn flipped_horizontally(self) -> Self {
+        let layout = self.layout.flipped_horizontally();
+        ByteCanvasMut { layout, ..self }
+    }
+
+    /// View the matrix rotated clockwise by 90 degrees, see [`StrideLayout::rotated_90`].
+    #[must_use = "This returns a new view of the same data."]
+    pub fn rotated_90(self) -> Self {
+        let layout = self.layout.rotated_90();
+        ByteCanvasMut { layout, ..self }
+    }
+
+    /// View the matrix rotated by 180 degrees, see [`StrideLayout::rotated_180`].
+    #[must_use = "This returns a new view of the same data."]
+    pub fn rotated_180(self) -> Self {
+        let layout = self.layout.rotated_180();
+        ByteCanvasMut { layout, ..self }
+    }
+
+    /// View the matrix rotated clockwise by 270 degrees, see [`StrideLayout::rotated_270`].
+    #[must_use = "This returns a new view of the same data."]
+    pub fn rotated_270(self) -> Self {
+        let layout = self.layout.rotated_270();
+        ByteCanvasMut { layout, ..self }
+    }
+}
+
+impl VolumeSpec {
+    /// The offset of the last slice.
+    ///
+    /// Slice offsets change linearly with the depth, so the first and last slice bound all others.
+    fn last_offset(&self) -> Option<isize> {
+        let max_d = isize::try_from(self.depth.checked_sub(1)?).ok()?;
+        let offset = isize::try_from(self.slice.offset).ok()?;
+        offset.checked_add(max_d.checked_mul(self.depth_stride)?)
+    }
+
+    /// The layout of a slice, which must be in bounds.
+    fn slice_at(&self, z: usize) -> StrideSpec {
+        // Can't overflow and is never negative, this was validated in `VolumeLayout::new`.
+        let offset = self.slice.offset as isize + (z as isize) * self.depth_stride;
+        StrideSpec {
+            offset: offset as usize,
+            ..self.slice
+        }
+    }
+}
+
+impl VolumeLayout {
+    /// Try to create a new layout from a specification.
+    ///
+    /// This fails for the same reasons as `StrideLayout::new`, applied to the whole volume, or if
+    /// the depth stride is not aligned to the element.
+    pub fn new(spec: VolumeSpec) -> Result<Self, BadStrideError> {
+        let slice = StrideLayout::new(spec.slice)?;
+
+        if spec.depth_stride % spec.slice.element.align() as isize != 0 {
+            return Err(BadStrideKind::UnalignedDepthStride.into());
+        }
+
+        if spec.depth == 0 {
+            // There are no slices, only the offset needs to be addressable.
+            let total = spec.slice.offset;
+            return Ok(VolumeLayout { spec, total });
+        }
+
+        // The first slice is valid, this validates the last one in the same way. Slices may be
+        // empty but their offsets must still be addressable.
+        let last = spec.last_offset().ok_or(BadStrideKind::OutOfMemory)?;
+        let (low, _) = spec
+            .slice
+            .relative_extent()
+            .ok_or(BadStrideKind::OutOfMemory)?;
+        last.checked_add(low)
+            .filter(|start| *start >= 0)
+            .ok_or(BadStrideKind::BeforeStart)?;
+
+        // Both are addressable, as the first slice is valid.
+        let first = spec.slice.offset as isize;
+        let past_end = slice.total as isize - first;
+        let total = first
+            .max(last)
+            .checked_add(past_end)
+            .and_then(|total| usize::try_from(total).ok())
+            .ok_or(BadStrideKind::OutOfMemory)?;
+
+        Ok(VolumeLayout { spec, total })
+    }
+
+    /// Construct from a packed stack of row major matrices.
+    ///
+    /// Returns `None` if the volume does not fit into memory.
+    pub fn with_row_major(matrix: layout::Matrix, depth: usize) -> Option<Self> {
+        let slice = StrideLayout::with_row_major(matrix).spec;
+        let total = matrix.byte_len().checked_mul(depth)?;
+        let depth_stride = isize::try_from(matrix.byte_len()).ok()?;
+        // No allocation can be larger, this also keeps all byte offsets expressible as `isize`.
+        isize::try_from(total).ok()?;
+
+        let spec = VolumeSpec {
+            slice,
+            depth,
+            depth_stride,
+        };
+        Some(VolumeLayout { spec, total })
+    }
+
+    /// Get the specification of this volume.
+    pub fn spec(&self) -> VolumeSpec {
+        self.spec
+    }
+
+    /// The number of slices.
+    pub fn depth(&self) -> usize {
+        self.spec.depth
+    }
+
+    /// Get the layout of one slice within the buffer of the volume.
+    ///
+    /// Returns `None` if `z` is not smaller than the depth.
+    pub fn slice(&self, z: usize) -> Option<StrideLayout> {
+        if z >= self.spec.depth {
+            return None;
+        }
+
+        let layout = StrideLayout::new(self.spec.slice_at(z));
+        Some(layout.expect("Slice is valid because the volume is valid"))
+    }
+
+    /// Iterate over the layouts of all slices, from front to back.
+    pub fn slices(&self) -> impl ExactSizeIterator<Item = StrideLayout> {
+        let volume = *self;
+        (0..self.spec.depth).map(move |z| volume.slice(z).unwrap())
+    }
+
+    fn matches(&self, element: layout::Element, width: usize, height: usize) -> bool {
+        let spec = self.spec.slice;
+        spec.element.size() == element.size() && spec.width == width && spec.height == height
+    }
+}
+
+/// Volumes of packed slices.
+impl Canvas<VolumeLayout> {
+    /// Stack matrices of equal dimensions into a packed volume.
+    ///
+    /// Returns `None` if the dimensions of the matrices differ.
+    ///
+    /// ```
+    /// use canvas::{Canvas, Matrix};
+    /// use canvas::stride::{ByteVolumeRef, VolumeLayout};
+    ///
+    /// let mut layers = vec![Matrix::<u8>::with_width_and_height(4, 2); 3];
+    /// layers[2][(3, 1)] = 0xff;
+    ///
+    /// let volume = Canvas::<VolumeLayout>::with_matrices(&layers).unwrap();
+    /// assert_eq!(volume.layout().depth(), 3);
+    /// assert_eq!(volume.as_bytes()[2 * 8 + 7], 0xff);
+    /// assert_eq!(ByteVolumeRef::new(&volume).to_matrices::<u8>(), layers);
+    /// ```
+    pub fn with_matrices<P: AsPixel>(matrices: &[Matrix<P>]) -> Option<Self> {
+        let (width, height) = matrices.first().map_or((0, 0), |matrix| {
+            (matrix.layout().width(), matrix.layout().height())
+        });
+
+        let element = layout::Element::from(P::pixel());
+        let matrix = layout::Matrix::from_width_height(element, width, height)?;
+        let layout = VolumeLayout::with_row_major(matrix, matrices.len())?;
+        if matrices.iter().any(|matrix| {
+            !layout.matches(element, matrix.layout().width(), matrix.layout().height())
+        }) {
+            return None;
+        }
+
+        let mut canvas = Canvas::new(layout);
+        ByteVolumeMut::new(&mut canvas).copy_from_matrices(matrices);
+        Some(canvas)
+    }
+}
+
+impl<'data> ByteVolumeRef<'data> {
+    /// Construct a reference to a volume canvas buffer.
+    pub fn new(canvas: &'data Canvas<VolumeLayout>) -> Self {
+        let layout = *canvas.layout();
+        let data = &canvas.as_bytes()[..layout.total];
+        ByteVolumeRef { layout, data }
+    }
+
+    /// View a byte slice as a volume.
+    ///
+    /// Returns `None` if the slice is too short for the layout.
+    pub fn with_bytes(layout: VolumeLayout, data: &'data [u8]) -> Option<Self> {
+        let data = data.get(..layout.total)?;
+        Some(ByteVolumeRef { layout, data })
+    }
+
+    /// Get the layout of this volume.
+    pub fn layout(&self) -> VolumeLayout {
+        self.layout
+    }
+
+    /// Get one slice of the volume.
+    ///
+    /// Returns `None` if `z` is not smaller than the depth.
+    pub fn slice(&self, z: usize) -> Option<ByteCanvasRef<'data>> {
+        let layout = self.layout.slice(z)?;
+        Some(ByteCanvasRef {
+            layout,
+            data: &self.data[..layout.total],
+        })
+    }
+
+    /// Iterate over all slices, from front to back.
+    pub fn slices(&self) -> impl ExactSizeIterator<Item = ByteCanvasRef<'data>> {
+        let data = self.data;
+        self.layout
+            .slices()
+            .map(move |layout| ByteCanvasRef { layout, data })
+    }
+
+    /// Copy each slice into a newly allocated, row-major matrix.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the size of the pixel type differs from the element size of this
+    /// volume or if the allocation fails.
+    pub fn to_matrices<P: AsPixel>(&self) -> Vec<Matrix<P>> {
+        self.slices().map(|slice| slice.to_matrix()).collect()
+    }
+}
+
+impl<'data> ByteVolumeMut<'data> {
+    /// Construct a mutable reference to a volume canvas buffer.
+    pub fn new(canvas: &'data mut Canvas<VolumeLayout>) -> Self {
+        let layout = *canvas.layout();
+        let data = &mut canvas.as_bytes_mut()[..layout.total];
+        ByteVolumeMut { layout, data }
+    }
+
+    /// View a mutable byte slice as a volume.
+    ///
+    /// Returns `None` if the slice is too short for the layout.
+    pub fn with_bytes(layout: VolumeLayout, data: &'data mut [u8]) -> Option<Self> {
+        let data = data.get_mut(..layout.total)?;
+        Some(ByteVolumeMut { layout, data })
+    }
+
+    /// Get the layout of this volume.
+    pub fn layout(&self) -> VolumeLayout {
+        self.layout
+    }
+
+    /// Get one slice of the volume mutably.
+    ///
+    /// Returns `None` if `z` is not smaller than the depth.
+    pub fn slice_mut(&mut self, z: usize) -> Option<ByteCanvasMut<'_>> {
+        let layout = self.layout.slice(z)?;
+        Some(ByteCanvasMut {
+            layout,
+            data: &mut self.data[..layout.total],
+        })
+    }
+
+    /// Copy one matrix into each slice, from front to back.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the number of matrices is not the depth of the volume or if the
+    /// dimensions or element sizes of a matrix and the slices differ.
+    pub fn copy_from_matrices<P: AsPixel>(&mut self, matrices: &[Matrix<P>]) {
+        assert_eq!(matrices.len(), self.layout.depth(), "Mismatching layouts.");
+        for (z, matrix) in matrices.iter().enumerate() {
+            let mut slice = self.slice_mut(z).unwrap();
+            slice.copy_from_canvas(ByteCanvasRef::from(matrix));
+        }
+    }
+
+    /// Get a read-only view of this volume.
+    pub fn as_ref(&self) -> ByteVolumeRef<'_> {
+        ByteVolumeRef {
+            layout: self.layout,
+            data: self.data,
+        }
+    }
 }
 
 /// Describes a rectangular matrix of pixels.
@@ -395,6 +1183,26 @@ pub trait Strided: Layout {
     fn strided(&self) -> StrideLayout;
 }
 
+fn tiled_matches(spec: &StrideSpec, tiled: &layout::Tiled) -> bool {
+    spec.width == tiled.width()
+        && spec.height == tiled.height()
+        && spec.element.size() == tiled.element().size()
+}
+
+/// Call a function with the layout of each tile and the coordinate of its first element.
+fn for_each_tile(tiled: &layout::Tiled, mut f: impl FnMut(StrideLayout, usize, usize)) {
+    for tile_y in 0..tiled.tiles_per_column() {
+        for tile_x in 0..tiled.tiles_per_row() {
+            let tile = tiled.tile_layout(tile_x, tile_y).unwrap();
+            f(
+                tile,
+                tile_x * tiled.tile_width(),
+                tile_y * tiled.tile_height(),
+            );
+        }
+    }
+}
+
 impl Layout for StrideLayout {
     fn byte_len(&self) -> usize {
         self.total
@@ -419,6 +1227,65 @@ impl<P: AsPixel> Strided for matrix::Layout<P> {
     }
 }
 
+/// Compare the elements, ignoring the strides and any bytes between elements.
+///
+/// Two canvases are equal if they have the same dimensions and element size and the bytes of the
+/// elements at each coordinate are equal.
+impl PartialEq for ByteCanvasRef<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        let (lhs, rhs) = (&self.layout.spec, &other.layout.spec);
+        if !lhs.matches(rhs) {
+            return false;
+        }
+
+        let row_wise = |spec: &StrideSpec| spec.element.size() as isize == spec.width_stride;
+        if row_wise(lhs) && row_wise(rhs) {
+            (0..lhs.height).all(|row| {
+                self.data[lhs.contiguous_row(row)] == other.data[rhs.contiguous_row(row)]
+            })
+        } else {
+            (0..lhs.height).all(|y| {
+                (0..lhs.width).all(|x| self.element_bytes(x, y) == other.element_bytes(x, y))
+            })
+        }
+    }
+}
+
+impl Eq for ByteCanvasRef<'_> {}
+
+/// Compare the elements, see the implementation for `ByteCanvasRef`.
+impl PartialEq for ByteCanvasMut<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_ref() == other.as_ref()
+    }
+}
+
+impl Eq for ByteCanvasMut<'_> {}
+
+/// View the pixels of a matrix as bytes.
+impl<'data, P: AsPixel> From<&'data Matrix<P>> for ByteCanvasRef<'data> {
+    fn from(matrix: &'data Matrix<P>) -> Self {
+        let layout = matrix.layout().strided();
+        let data = &matrix.as_bytes()[..layout.total];
+        ByteCanvasRef { layout, data }
+    }
+}
+
+/// View the pixels of a matrix as mutable bytes.
+impl<'data, P: AsPixel> From<&'data mut Matrix<P>> for ByteCanvasMut<'data> {
+    fn from(matrix: &'data mut Matrix<P>) -> Self {
+        let layout = matrix.layout().strided();
+        let data = &mut matrix.as_bytes_mut()[..layout.total];
+        ByteCanvasMut { layout, data }
+    }
+}
+
+impl Layout for VolumeLayout {
+    fn byte_len(&self) -> usize {
+        self.total
+    }
+}
+
 impl From<BadStrideKind> for BadStrideError {
     fn from(kind: BadStrideKind) -> Self {
         BadStrideError { kind }
@@ -465,3 +1332,286 @@ fn canvas_copies() {
         "In transposed matrix order"
     );
 }
+
+#[test]
+fn matrix_texels() {
+    let matrix = Matrix::<[u8; 4]>::with_width_and_height(2, 2);
+    let texel = <[u8; 4]>::texel();
+    assert!(texel.is_some());
+
+    assert_eq!(matrix.layout().strided().spec().element.texel(), texel);
+    assert_eq!(
+        ByteCanvasRef::from(&matrix).layout().spec().element.texel(),
+        texel
+    );
+
+    // Without `AsPixel` the texel is unknown, all conversions agree on the element.
+    let typed = layout::TMatrix::empty(<[u8; 4]>::pixel());
+    let converted = StrideLayout::with_row_major(typed.into());
+    let decayed = StrideLayout::with_row_major(layout::Decay::decay(typed));
+    let into = StrideLayout::with_row_major(typed.into_matrix());
+    assert!(converted == into && decayed == into);
+    assert_eq!(into.spec().element.texel(), None);
+}
+
+#[test]
+fn negative_strides() {
+    let matrix = layout::Matrix::from_width_height(layout::Element::from_pixel::<u8>(), 3, 2)
+        .expect("Valid matrix");
+    let row_layout = StrideLayout::with_row_major(matrix);
+
+    let bottom_up = StrideLayout::new(StrideSpec {
+        height_stride: -3,
+        offset: 3,
+        ..row_layout.spec()
+    })
+    .expect("Valid bottom-up layout");
+    assert_eq!(bottom_up.byte_len(), 6);
+
+    let mirrored = StrideLayout::new(StrideSpec {
+        width_stride: -1,
+        offset: 2,
+        ..row_layout.spec()
+    })
+    .expect("Valid mirrored layout");
+    assert_eq!(mirrored.byte_len(), 6);
+
+    let before_start = StrideSpec {
+        width_stride: -1,
+        offset: 1,
+        ..row_layout.spec()
+    };
+    assert!(StrideLayout::new(before_start).is_err());
+
+    let src = Canvas::with_bytes(row_layout, &[0u8, 1, 2, 3, 4, 5]);
+
+    let mut dst = Canvas::new(bottom_up);
+    ByteCanvasMut::new(&mut dst).copy_from_canvas(ByteCanvasRef::new(&src));
+    assert_eq!(
+        dst.as_bytes(),
+        &[3u8, 4, 5, 0, 1, 2],
+        "Rows in reverse order"
+    );
+
+    let mut dst = Canvas::new(mirrored);
+    ByteCanvasMut::new(&mut dst).copy_from_canvas(ByteCanvasRef::new(&src));
+    assert_eq!(
+        dst.as_bytes(),
+        &[2u8, 1, 0, 5, 4, 3],
+        "Columns in reverse order"
+    );
+
+    // Copying between two mirrored images can use whole rows.
+    let mut back = Canvas::new(mirrored);
+    ByteCanvasMut::new(&mut back).copy_from_canvas(ByteCanvasRef::new(&dst));
+    assert_eq!(back.as_bytes(), dst.as_bytes());
+}
+
+#[test]
+fn layout_transforms() {
+    let matrix = layout::Matrix::from_width_height(layout::Element::from_pixel::<u8>(), 3, 2)
+        .expect("Valid matrix");
+    let layout = StrideLayout::with_row_major(matrix);
+    // 0 1 2
+    // 3 4 5
+    let src = Canvas::with_bytes(layout, &[0u8, 1, 2, 3, 4, 5]);
+    let view = ByteCanvasRef::new(&src);
+
+    let transposed = view.as_ref().transposed().to_matrix::<u8>();
+    assert_eq!(transposed.as_slice(), &[0u8, 3, 1, 4, 2, 5]);
+
+    let vertical = view.as_ref().flipped_vertically().to_matrix::<u8>();
+    assert_eq!(vertical.as_slice(), &[3u8, 4, 5, 0, 1, 2]);
+
+    let horizontal = view.as_ref().flipped_horizontally().to_matrix::<u8>();
+    assert_eq!(horizontal.as_slice(), &[2u8, 1, 0, 5, 4, 3]);
+
+    let rotated = view.as_ref().rotated_90().to_matrix::<u8>();
+    assert_eq!(rotated.as_slice(), &[3u8, 0, 4, 1, 5, 2]);
+
+    let rotated = view.as_ref().rotated_180().to_matrix::<u8>();
+    assert_eq!(rotated.as_slice(), &[5u8, 4, 3, 2, 1, 0]);
+
+    let rotated = view.as_ref().rotated_270().to_matrix::<u8>();
+    assert_eq!(rotated.as_slice(), &[2u8, 5, 1, 4, 0, 3]);
+
+    assert!(layout.rotated_90().rotated_270() == layout);
+    assert!(layout.transposed().transposed() == layout);
+    assert_eq!(layout.rotated_180().byte_len(), layout.byte_len());
+}
+
+#[test]
+fn fills() {
+    let matrix = layout::Matrix::from_width_height(layout::Element::from_pixel::<u16>(), 3, 2)
+        .expect("Valid matrix");
+    let row_layout = StrideLayout::with_row_major(matrix);
+    let col_layout = StrideLayout::with_column_major(matrix);
+    let bottom_up = StrideLayout::new(StrideSpec {
+        height_stride: -6,
+        offset: 6,
+        ..row_layout.spec()
+    })
+    .expect("Valid bottom-up layout");
+
+    let mut canvas = Canvas::new(row_layout);
+    ByteCanvasMut::new(&mut canvas).fill(&[1, 2]);
+    assert_eq!(canvas.as_bytes(), &[1, 2, 1, 2, 1, 2, 1, 2, 1, 2, 1, 2]);
+
+    let rect = Rect {
+        x: 1,
+        y: 1,
+        width: 4,
+        height: 4,
+    };
+
+    let mut canvas = Canvas::new(col_layout);
+    ByteCanvasMut::new(&mut canvas).fill_rect(rect, &[1, 1]);
+    assert_eq!(canvas.as_bytes(), &[0, 0, 0, 0, 0, 0, 1, 1, 0, 0, 1, 1]);
+
+    let mut canvas = Canvas::new(bottom_up);
+    ByteCanvasMut::new(&mut canvas).fill_rect(rect, &[1, 1]);
+    assert_eq!(canvas.as_bytes(), &[0, 0, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0]);
+
+    let mut canvas = Canvas::new(bottom_up);
+    ByteCanvasMut::new(&mut canvas).fill_with(|c| [c.x() as u8, c.y() as u8]);
+    assert_eq!(canvas.as_bytes(), &[0, 1, 1, 1, 2, 1, 0, 0, 1, 0, 2, 0]);
+
+    // A non-contiguous layout, every other element of a row.
+    let sparse = StrideLayout::new(StrideSpec {
+        width: 2,
+        width_stride: 4,
+        ..row_layout.spec()
+    })
+    .expect("Valid sparse layout");
+    let mut canvas = Canvas::new(sparse);
+    ByteCanvasMut::new(&mut canvas).fill(&[1, 1]);
+    assert_eq!(&canvas.as_bytes()[..10], &[1, 1, 0, 0, 1, 1, 1, 1, 0, 0]);
+}
+
+#[test]
+fn tiles() {
+    use crate::pixel::constants::U16;
+
+    let mut matrix = Matrix::<u16>::with_width_and_height(5, 3);
+    for (idx, p) in matrix.as_mut_slice().iter_mut().enumerate() {
+        *p = idx as u16;
+    }
+
+    let tiled = layout::Tiled::new(U16.into(), 5, 3, 4, 2).unwrap();
+    assert_eq!(tiled.byte_len(), 4 * 4 * 2 * 2);
+    let mut bytes = [0xffu8; 64];
+    ByteCanvasRef::from(&matrix).copy_to_tiled(&tiled, &mut bytes);
+
+    let words: &[u16] = bytemuck::cast_slice(&bytes);
+    let padding = u16::MAX;
+    assert_eq!(&words[..8], &[0, 1, 2, 3, 5, 6, 7, 8]);
+    assert_eq!(
+        &words[8..16],
+        &[4, padding, padding, padding, 9, padding, padding, padding]
+    );
+    assert_eq!(&words[16..20], &[10, 11, 12, 13]);
+    assert_eq!(words[24], 14);
+
+    let offset = tiled.offset(4, 2).unwrap();
+    assert_eq!(&bytes[offset..offset + 2], &14u16.to_ne_bytes());
+    assert_eq!(tiled.offset(5, 0), None);
+
+    // Untile into a transposed view, which is the transposed matrix.
+    let mut transposed = Matrix::<u16>::with_width_and_height(3, 5);
+    ByteCanvasMut::from(&mut transposed)
+        .transposed()
+        .copy_from_tiled(&tiled, &bytes);
+    assert_eq!(transposed[(2, 4)], matrix[(4, 2)]);
+    assert_eq!(transposed[(1, 3)], matrix[(3, 1)]);
+
+    let mut roundtrip = Matrix::<u16>::with_width_and_height(5, 3);
+    ByteCanvasMut::from(&mut roundtrip).copy_from_tiled(&tiled, &bytes);
+    assert_eq!(roundtrip, matrix);
+}
+
+#[test]
+fn volumes() {
+    use crate::pixel::constants::U16;
+
+    let layers: Vec<_> = (0..3u16)
+        .map(|z| {
+            let mut matrix = Matrix::<u16>::with_width_and_height(3, 2);
+            for (idx, p) in matrix.as_mut_slice().iter_mut().enumerate() {
+                *p = 10 * z + idx as u16;
+            }
+            matrix
+        })
+        .collect();
+
+    let volume = Canvas::<VolumeLayout>::with_matrices(&layers).unwrap();
+    assert_eq!(volume.layout().byte_len(), 3 * 3 * 2 * 2);
+    let slice = volume.layout().slice(1).unwrap();
+    assert_eq!(slice.spec().offset, 12);
+    assert!(volume.layout().slice(3).is_none());
+
+    let bytes = ByteVolumeRef::new(&volume);
+    assert_eq!(bytes.slices().len(), 3);
+    assert!(bytes.slice(2).unwrap() == ByteCanvasRef::from(&layers[2]));
+    assert_eq!(bytes.to_matrices::<u16>(), layers);
+
+    let mismatched = [layers[0].clone(), Matrix::with_width_and_height(2, 3)];
+    assert!(Canvas::<VolumeLayout>::with_matrices(&mismatched).is_none());
+
+    // Slices stored back to front, with padding between them.
+    let matrix = layout::Matrix::from_width_height(U16.into(), 3, 2).unwrap();
+    let reversed = VolumeSpec {
+        slice: StrideSpec {
+            offset: 32,
+            ..StrideLayout::with_row_major(matrix).spec()
+        },
+        depth: 3,
+        depth_stride: -16,
+    };
+    let reversed = VolumeLayout::new(reversed).unwrap();
+    assert_eq!(reversed.byte_len(), 44);
+
+    let mut data = [0u8; 44];
+    let mut target = ByteVolumeMut::with_bytes(reversed, &mut data).unwrap();
+    target.copy_from_matrices(&layers);
+    assert_eq!(target.as_ref().to_matrices::<u16>(), layers);
+    let words: &[u16] = bytemuck::cast_slice(&data);
+    assert_eq!(&words[..6], &[20, 21, 22, 23, 24, 25]);
+    assert_eq!(&words[16..], &[0, 1, 2, 3, 4, 5]);
+
+    let too_far = VolumeSpec {
+        depth: 4,
+        ..reversed.spec()
+    };
+    assert!(VolumeLayout::new(too_far).is_err());
+    let unaligned = VolumeSpec {
+        depth_stride: -15,
+        ..reversed.spec()
+    };
+    assert!(VolumeLayout::new(unaligned).is_err());
+
+    // Empty slices still have offsets that must lie within the buffer.
+    let empty = VolumeSpec {
+        slice: StrideSpec {
+            width: 0,
+            offset: 0,
+            ..reversed.spec().slice
+        },
+        depth: 3,
+        depth_stride: -16,
+    };
+    assert!(VolumeLayout::new(empty).is_err());
+
+    let empty = VolumeLayout::new(VolumeSpec {
+        depth_stride: 16,
+        ..empty
+    })
+    .unwrap();
+    assert_eq!(empty.byte_len(), 32);
+    assert_eq!(empty.slice(2).unwrap().spec().offset, 32);
+    let data = [0u8; 32];
+    let matrices = ByteVolumeRef::with_bytes(empty, &data)
+        .unwrap()
+        .to_matrices::<u16>();
+    assert_eq!(matrices.len(), 3);
+    assert!(matrices.iter().all(|matrix| matrix.as_slice().is_empty()));
+}
@@ -0,0 +1,299 @@
+//! Conversion between channel orders of the same family of formats.
+//!
+//! The packed RGB formats of DRM come in every permutation of their channels, such as `XRGB8888`,
+//! `BGRX8888` or `ABGR8888`. A [`Swizzle`] reorders the channels between two such formats where
+//! all channels have the same sizes, that is within the 8888, the 2101010 and the 16161616F
+//! family. Converting from a format with an unused `X` channel to one with an alpha channel makes
+//! all pixels opaque. The formats may also differ in their byte order, see
+//! [`FourCC::big_endian`](crate::drm::FourCC::big_endian).
+//!
+//! ```
+//! use canvas::Matrix;
+//! use canvas::drm::FourCC;
+//! use canvas::swizzle::Swizzle;
+//!
+//! let mut matrix = Matrix::<[u8; 4]>::with_width_and_height(1, 1);
+//! // Blue, green, red and the unused byte, in memory.
+//! matrix[(0, 0)] = [0x00, 0x80, 0xff, 0x00];
+//!
+//! let swizzle = Swizzle::new(FourCC::XRGB8888, FourCC::ABGR8888).unwrap();
+//! swizzle.apply_in_place((&mut matrix).into());
+//! assert_eq!(matrix[(0, 0)], [0xff, 0x80, 0x00, 0xff]);
+//! ```
+use core::convert::TryInto;
+
+use crate::drm::FourCC;
+use crate::stride::{ByteCanvasMut, ByteCanvasRef};
+
+/// A conversion between the channel orders of two formats.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Swizzle {
+    from: Order,
+    to: Order,
+}
+
+/// An error indicating that two formats can not be swizzled into each other.
+#[derive(Debug, Default, PartialEq, Eq, Hash)]
+pub struct UnsupportedSwizzleError {
+    _private: (),
+}
+
+/// The channels of a packed format.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct Order {
+    family: Family,
+    /// The shifts of red, green, blue and the alpha or unused channel.
+    shifts: [u32; 4],
+    has_alpha: bool,
+    big_endian: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Family {
+    Rgb8888,
+    Rgb2101010,
+    Rgb16161616F,
+}
+
+impl Swizzle {
+    /// Create a conversion between two formats of the same family.
+    ///
+    /// Fails if either format is not a packed RGB format of a supported family or if the two
+    /// formats do not belong to the same family.
+    pub fn new(from: FourCC, to: FourCC) -> Result<Self, UnsupportedSwizzleError> {
+        let from = Order::from_fourcc(from).ok_or_else(UnsupportedSwizzleError::default)?;
+        let to = Order::from_fourcc(to).ok_or_else(UnsupportedSwizzleError::default)?;
+
+        if from.family != to.family {
+            return Err(UnsupportedSwizzleError::default());
+        }
+
+        Ok(Swizzle { from, to })
+    }
+
+    /// The size of elements of both formats, in bytes.
+    pub fn element_size(&self) -> usize {
+        self.from.family.bytes()
+    }
+
+    /// Convert all elements of a canvas in place.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the element size of the canvas is not that of the formats.
+    pub fn apply_in_place(&self, mut canvas: ByteCanvasMut<'_>) {
+        let spec = canvas.layout().spec();
+        assert_eq!(
+            spec.element.size(),
+            self.element_size(),
+            "Mismatching pixel size."
+        );
+
+        for y in 0..spec.height {
+            for x in 0..spec.width {
+                let element = canvas.element_bytes_mut(x, y);
+                let value = self.convert(self.from.read(element));
+                self.to.write(element, value);
+            }
+        }
+    }
+
+    /// Convert all elements of a canvas into another.
+    ///
+    /// Both canvas must have the same width and height but may differ in their strides.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the element size of either canvas is not that of the formats or the
+    /// dimensions of the canvases differ.
+    pub fn apply(&self, source: ByteCanvasRef<'_>, mut target: ByteCanvasMut<'_>) {
+        let (src, spec) = (source.layout().spec(), target.layout().spec());
+        assert_eq!(
+            src.element.size(),
+            self.element_size(),
+            "Mismatching pixel size."
+        );
+        assert_eq!(
+            spec.element.size(),
+            self.element_size(),
+            "Mismatching pixel size."
+        );
+        assert!(
+            (src.width, src.height) == (spec.width, spec.height),
+            "Mismatching dimensions."
+        );
+
+        for y in 0..spec.height {
+            for x in 0..spec.width {
+                let value = self.convert(self.from.read(source.element_bytes(x, y)));
+                self.to.write(target.element_bytes_mut(x, y), value);
+            }
+        }
+    }
+
+    fn convert(&self, value: u64) -> u64 {
+        let (color_mask, alpha_mask) = self.from.family.masks();
+        let mut result = 0;
+
+        for (&from, &to) in self.from.shifts[..3].iter().zip(&self.to.shifts[..3]) {
+            result |= ((value >> from) & color_mask) << to;
+        }
+
+        let alpha = if self.to.has_alpha && !self.from.has_alpha {
+            self.from.family.opaque()
+        } else {
+            (value >> self.from.shifts[3]) & alpha_mask
+        };
+
+        result | alpha << self.to.shifts[3]
+    }
+}
+
+impl Order {
+    fn from_fourcc(fourcc: FourCC) -> Option<Self> {
+        let big_endian = fourcc.is_big_endian();
+        let new = |family, shifts, has_alpha| Order {
+            family,
+            shifts,
+            has_alpha,
+            big_endian,
+        };
+
+        use Family::*;
+        Some(match fourcc.little_endian() {
+            FourCC::XRGB8888 => new(Rgb8888, [16, 8, 0, 24], false),
+            FourCC::XBGR8888 => new(Rgb8888, [0, 8, 16, 24], false),
+            FourCC::RGBX8888 => new(Rgb8888, [24, 16, 8, 0], false),
+            FourCC::BGRX8888 => new(Rgb8888, [8, 16, 24, 0], false),
+            FourCC::ARGB8888 => new(Rgb8888, [16, 8, 0, 24], true),
+            FourCC::ABGR8888 => new(Rgb8888, [0, 8, 16, 24], true),
+            FourCC::RGBA8888 => new(Rgb8888, [24, 16, 8, 0], true),
+            FourCC::BGRA8888 => new(Rgb8888, [8, 16, 24, 0], true),
+            FourCC::XRGB2101010 => new(Rgb2101010, [20, 10, 0, 30], false),
+            FourCC::XBGR2101010 => new(Rgb2101010, [0, 10, 20, 30], false),
+            FourCC::RGBX1010102 => new(Rgb2101010, [22, 12, 2, 0], false),
+            FourCC::BGRX1010102 => new(Rgb2101010, [2, 12, 22, 0], false),
+            FourCC::ARGB2101010 => new(Rgb2101010, [20, 10, 0, 30], true),
+            FourCC::ABGR2101010 => new(Rgb2101010, [0, 10, 20, 30], true),
+            FourCC::RGBA1010102 => new(Rgb2101010, [22, 12, 2, 0], true),
+            FourCC::BGRA1010102 => new(Rgb2101010, [2, 12, 22, 0], true),
+            FourCC::XRGB16161616F => new(Rgb16161616F, [32, 16, 0, 48], false),
+            FourCC::XBGR16161616F => new(Rgb16161616F, [0, 16, 32, 48], false),
+            FourCC::ARGB16161616F => new(Rgb16161616F, [32, 16, 0, 48], true),
+            FourCC::ABGR16161616F => new(Rgb16161616F, [0, 16, 32, 48], true),
+            _ => return None,
+        })
+    }
+
+    fn read(&self, element: &[u8]) -> u64 {
+        match (element.len(), self.big_endian) {
+            (4, false) => u64::from(u32::from_le_bytes(element.try_into().unwrap())),
+            (4, true) => u64::from(u32::from_be_bytes(element.try_into().unwrap())),
+            (_, false) => u64::from_le_bytes(element.try_into().unwrap()),
+            (_, true) => u64::from_be_bytes(element.try_into().unwrap()),
+        }
+    }
+
+    fn write(&self, element: &mut [u8], value: u64) {
+        match (element.len(), self.big_endian) {
+            (4, false) => element.copy_from_slice(&(value as u32).to_le_bytes()),
+            (4, true) => element.copy_from_slice(&(value as u32).to_be_bytes()),
+            (_, false) => element.copy_from_slice(&value.to_le_bytes()),
+            (_, true) => element.copy_from_slice(&value.to_be_bytes()),
+        }
+    }
+}
+
+impl Family {
+    fn bytes(self) -> usize {
+        match self {
+            Family::Rgb8888 | Family::Rgb2101010 => 4,
+            Family::Rgb16161616F => 8,
+        }
+    }
+
+    /// The masks of a color and the alpha channel.
+    fn masks(self) -> (u64, u64) {
+        match self {
+            Family::Rgb8888 => (0xff, 0xff),
+            Family::Rgb2101010 => (0x3ff, 0x3),
+            Family::Rgb16161616F => (0xffff, 0xffff),
+        }
+    }
+
+    /// The value of a fully opaque alpha channel.
+    fn opaque(self) -> u64 {
+        match self {
+            Family::Rgb16161616F => 0x3c00,
+            _ => self.masks().1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Matrix;
+
+    #[test]
+    fn families() {
+        assert!(Swizzle::new(FourCC::XRGB8888, FourCC::RGBA8888).is_ok());
+        assert!(Swizzle::new(FourCC::ARGB2101010, FourCC::BGRX1010102).is_ok());
+        assert!(Swizzle::new(FourCC::XRGB8888, FourCC::XRGB2101010).is_err());
+        assert!(Swizzle::new(FourCC::RGB888, FourCC::BGR888).is_err());
+    }
+
+    #[test]
+    fn byte_order() {
+        let mut matrix = Matrix::<[u8; 4]>::with_width_and_height(1, 1);
+        matrix[(0, 0)] = [0x00, 0x80, 0xff, 0x00];
+
+        let swizzle = Swizzle::new(FourCC::XRGB8888, FourCC::ARGB8888.big_endian()).unwrap();
+        swizzle.apply_in_place((&mut matrix).into());
+        assert_eq!(matrix[(0, 0)], [0xff, 0xff, 0x80, 0x00]);
+    }
+
+    #[test]
+    fn roundtrip() {
+        let orders = [
+            FourCC::ARGB2101010,
+            FourCC::ABGR2101010,
+            FourCC::RGBA1010102,
+            FourCC::BGRA1010102,
+        ];
+
+        let original = 0b10 << 30 | 0x3ff << 20 | 0x155 << 10 | 0x001;
+        for &from in &orders {
+            for &to in &orders {
+                let mut matrix = Matrix::<u32>::with_width_and_height(1, 1);
+                matrix[(0, 0)] = original;
+                let there = Swizzle::new(FourCC::ARGB2101010, from).unwrap();
+                there.apply_in_place((&mut matrix).into());
+                Swizzle::new(from, to)
+                    .unwrap()
+                    .apply_in_place((&mut matrix).into());
+                let back = Swizzle::new(to, FourCC::ARGB2101010).unwrap();
+                back.apply_in_place((&mut matrix).into());
+                assert_eq!(matrix[(0, 0)], original, "{:?} {:?}", from, to);
+            }
+        }
+    }
+
+    #[test]
+    fn opaque_alpha() {
+        let pixel = crate::pixel::constants::U16.array4();
+        let layout = crate::Layout::width_and_height_for_pixel(pixel, 1, 1).unwrap();
+        let mut source = Matrix::with_layout(layout);
+        // Red, green and blue of 1.0 and -2.0 with garbage in the unused channel.
+        source[(0, 0)] = [0x3c00, 0xc000, 0x3c00, 0x1234];
+        let mut target = source.clone();
+
+        let swizzle = Swizzle::new(FourCC::XRGB16161616F, FourCC::ABGR16161616F).unwrap();
+        swizzle.apply((&source).into(), (&mut target).into());
+        assert_eq!(target[(0, 0)], [0x3c00, 0xc000, 0x3c00, 0x3c00]);
+
+        let swizzle = Swizzle::new(FourCC::ARGB16161616F, FourCC::XBGR16161616F).unwrap();
+        swizzle.apply((&source).into(), (&mut target).into());
+        assert_eq!(target[(0, 0)], [0x3c00, 0xc000, 0x3c00, 0x1234]);
+    }
+}
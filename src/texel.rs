@@ -0,0 +1,129 @@
+//! Descriptors of the channels within an element.
+
+/// The numeric interpretation of the channels of a texel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SampleKind {
+    /// Unsigned integers, such as indices or raw counts.
+    UInt,
+    /// Signed integers.
+    SInt,
+    /// Unsigned integers normalized to the range from zero to one by their maximum value.
+    UNorm,
+    /// IEEE floating point numbers.
+    Float,
+}
+
+/// The meaning and order of the channels of a texel.
+///
+/// The channels are listed starting at the least significant bit when the block is read as an
+/// integer in the byte order of its format. For an array of bytes this is simply their order in
+/// memory. Names in the form of `Xrgb` mark an unused channel with `X`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Channels {
+    /// The channels have no known meaning.
+    Unspecified,
+    /// An index into a palette.
+    Index,
+    /// A single gray channel.
+    Gray,
+    /// Gray and alpha.
+    GrayAlpha,
+    /// A single alpha channel.
+    Alpha,
+    /// Red, green and blue.
+    Rgb,
+    /// Blue, green and red.
+    Bgr,
+    /// Red, green, blue and alpha.
+    Rgba,
+    /// Blue, green, red and alpha.
+    Bgra,
+    /// Alpha, red, green and blue.
+    Argb,
+    /// Alpha, blue, green and red.
+    Abgr,
+    /// Red, green, blue and an unused channel.
+    Rgbx,
+    /// Blue, green, red and an unused channel.
+    Bgrx,
+    /// An unused channel, red, green and blue.
+    Xrgb,
+    /// An unused channel, blue, green and red.
+    Xbgr,
+    /// The luma channel of a planar format.
+    Luma,
+    /// The blue difference chroma channel of a planar format.
+    Cb,
+    /// The red difference chroma channel of a planar format.
+    Cr,
+    /// Both chroma channels of a semi-planar format.
+    CbCr,
+    /// Both chroma channels of a semi-planar format, in swapped order.
+    CrCb,
+    /// Packed luma and chroma.
+    YCbCr,
+    /// Packed chroma, luma and alpha.
+    CrCbYA,
+    /// Packed chroma, luma and an unused channel.
+    CrCbYX,
+    /// Two horizontally adjacent pixels, sharing one pair of chroma samples.
+    Yuyv,
+    /// Two horizontally adjacent pixels, sharing one pair of chroma samples in swapped order.
+    Yvyu,
+}
+
+/// A description of the channels within an element.
+///
+/// This is advisory information that lets generic code choose the right interpretation of bytes
+/// at runtime. It is derived from the pixel constants and from the DRM format infos.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Texel {
+    /// The numeric type of all channels.
+    pub kind: SampleKind,
+    /// The meaning of the channels, in order.
+    pub channels: Channels,
+    /// The number of bits of each channel, in order, with zero for missing channels.
+    pub bits: [u8; 4],
+}
+
+impl Texel {
+    /// Create a texel descriptor.
+    pub const fn new(kind: SampleKind, channels: Channels, bits: [u8; 4]) -> Self {
+        Texel {
+            kind,
+            channels,
+            bits,
+        }
+    }
+
+    /// A texel of `count` channels with the same number of bits each.
+    pub(crate) const fn uniform(kind: SampleKind, channels: Channels, count: u8, bits: u8) -> Self {
+        let bits = [
+            bits,
+            if count > 1 { bits } else { 0 },
+            if count > 2 { bits } else { 0 },
+            if count > 3 { bits } else { 0 },
+        ];
+        Texel::new(kind, channels, bits)
+    }
+
+    /// The number of channels.
+    pub fn channel_count(self) -> usize {
+        self.bits.iter().filter(|&&b| b != 0).count()
+    }
+
+    /// The total number of bits of all channels.
+    pub fn bits_per_texel(self) -> u32 {
+        self.bits.iter().map(|&b| u32::from(b)).sum()
+    }
+
+    /// The index of the alpha channel, if there is one.
+    pub fn alpha_position(self) -> Option<usize> {
+        match self.channels {
+            Channels::Alpha | Channels::Argb | Channels::Abgr => Some(0),
+            Channels::GrayAlpha => Some(1),
+            Channels::Rgba | Channels::Bgra | Channels::CrCbYA => Some(3),
+            _ => None,
+        }
+    }
+}
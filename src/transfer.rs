@@ -0,0 +1,402 @@
+//! Conversion of color channels between encoded and linear light.
+//!
+//! Most images store their color channels non-linearly, encoded with a transfer function. Any
+//! arithmetic that mixes colors, such as scaling or blending, should instead happen on linear
+//! values. The conversions here apply to the first three channels of RGBA pixels and leave the
+//! alpha channel untouched. For 8-bit and 16-bit channels a [`TransferLut`] evaluates the function
+//! once per possible value into a lookup table, which can be reused for any number of images.
+//!
+//! ```
+//! use canvas::Matrix;
+//! use canvas::transfer::{self, TransferFunction, TransferLut};
+//!
+//! let mut matrix = Matrix::<[u8; 4]>::with_width_and_height(1, 1);
+//! matrix[(0, 0)] = [0xff, 0x80, 0x00, 0x80];
+//!
+//! transfer::to_linear(&mut matrix, TransferFunction::Srgb);
+//! assert_eq!(matrix[(0, 0)], [0xff, 0x37, 0x00, 0x80]);
+//!
+//! let encode = TransferLut::from_linear(TransferFunction::Srgb);
+//! encode.apply(&mut matrix);
+//! assert_eq!(matrix[(0, 0)], [0xff, 0x80, 0x00, 0x80]);
+//! ```
+use alloc::vec::Vec;
+
+use crate::color::TransferCharacteristics;
+use crate::Matrix;
+
+/// A transfer function between encoded values and linear light.
+///
+/// All values are normalized such that the nominal range of both encoded and linear values is
+/// from zero to one. Negative inputs are treated as zero.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TransferFunction {
+    /// The piece-wise sRGB function of IEC 61966-2-1.
+    Srgb,
+    /// The reference display of ITU-R BT.1886 for a black level of zero, a pure power of 2.4.
+    ///
+    /// This is the display function intended for BT.601, BT.709 and BT.2020 video.
+    Bt1886,
+    /// The perceptual quantizer of SMPTE ST 2084 and BT.2100.
+    ///
+    /// A linear value of one corresponds to a luminance of 10000 cd/m².
+    Pq,
+    /// The hybrid log-gamma of ARIB STD-B67 and BT.2100.
+    ///
+    /// Linear values are relative scene light, the inverse of the opto-electronic function.
+    Hlg,
+}
+
+/// A channel type that can be converted with a transfer function.
+///
+/// Integer channels are normalized to their maximum value.
+pub trait TransferSample: Copy {
+    /// The number of entries of a table holding the result for every channel value.
+    ///
+    /// This is zero for types with too many values, which are always converted directly.
+    const TABLE_LEN: usize;
+    /// Apply a function of normalized values to a channel value.
+    fn evaluate(self, function: impl Fn(f32) -> f32) -> Self;
+    /// The channel value at an index of the table.
+    fn from_index(index: usize) -> Self;
+    /// The index of a channel value in the table.
+    fn index(self) -> usize;
+}
+
+/// A transfer function, precomputed for all values of a channel type.
+///
+/// Building the table evaluates the function once for each of the `TABLE_LEN` values, that is
+/// 65536 times for `u16`. Keep the converter around to convert several images with it.
+#[derive(Clone, Debug)]
+pub struct TransferLut<S> {
+    function: TransferFunction,
+    decode: bool,
+    table: Vec<S>,
+}
+
+impl TransferFunction {
+    /// Select the function for transfer characteristics, if it is supported.
+    ///
+    /// Video transfer characteristics are mapped to the display function of BT.1886.
+    pub fn from_characteristics(characteristics: TransferCharacteristics) -> Option<Self> {
+        use TransferCharacteristics as T;
+        Some(match characteristics {
+            T::Srgb => TransferFunction::Srgb,
+            T::Bt709 | T::Smpte170M | T::Bt2020_10 | T::Bt2020_12 => TransferFunction::Bt1886,
+            T::Pq => TransferFunction::Pq,
+            T::Hlg => TransferFunction::Hlg,
+            _ => return None,
+        })
+    }
+
+    /// Convert an encoded value to linear light.
+    pub fn to_linear(self, encoded: f32) -> f32 {
+        let v = encoded.max(0.0);
+        match self {
+            TransferFunction::Srgb => {
+                if v <= 0.04045 {
+                    v / 12.92
+                } else {
+                    libm::powf((v + 0.055) / 1.055, 2.4)
+                }
+            }
+            TransferFunction::Bt1886 => libm::powf(v, 2.4),
+            TransferFunction::Pq => {
+                let p = libm::powf(v, 1.0 / PQ_M2);
+                let num = (p - PQ_C1).max(0.0);
+                libm::powf(num / (PQ_C2 - PQ_C3 * p), 1.0 / PQ_M1)
+            }
+            TransferFunction::Hlg => {
+                if v <= 0.5 {
+                    v * v / 3.0
+                } else {
+                    (libm::expf((v - HLG_C) / HLG_A) + HLG_B) / 12.0
+                }
+            }
+        }
+    }
+
+    /// Convert linear light to an encoded value.
+    pub fn from_linear(self, linear: f32) -> f32 {
+        let l = linear.max(0.0);
+        match self {
+            TransferFunction::Srgb => {
+                if l <= 0.003_130_8 {
+                    l * 12.92
+                } else {
+                    1.055 * libm::powf(l, 1.0 / 2.4) - 0.055
+                }
+            }
+            TransferFunction::Bt1886 => libm::powf(l, 1.0 / 2.4),
+            TransferFunction::Pq => {
+                let y = libm::powf(l, PQ_M1);
+                libm::powf((PQ_C1 + PQ_C2 * y) / (1.0 + PQ_C3 * y), PQ_M2)
+            }
+            TransferFunction::Hlg => {
+                if l <= 1.0 / 12.0 {
+                    libm::sqrtf(3.0 * l)
+                } else {
+                    HLG_A * libm::logf(12.0 * l - HLG_B) + HLG_C
+                }
+            }
+        }
+    }
+}
+
+const PQ_M1: f32 = 2610.0 / 16384.0;
+const PQ_M2: f32 = 2523.0 / 4096.0 * 128.0;
+const PQ_C1: f32 = 3424.0 / 4096.0;
+const PQ_C2: f32 = 2413.0 / 4096.0 * 32.0;
+const PQ_C3: f32 = 2392.0 / 4096.0 * 32.0;
+
+const HLG_A: f32 = 0.178_832_77;
+const HLG_B: f32 = 0.284_668_92;
+const HLG_C: f32 = 0.559_910_7;
+
+/// Convert the color channels of a matrix from encoded values to linear light.
+///
+/// This builds a table for large matrices on every call, use a [`TransferLut`] to convert more
+/// than one image.
+pub fn to_linear<S: TransferSample>(matrix: &mut Matrix<[S; 4]>, function: TransferFunction) {
+    convert_once(matrix.as_mut_slice(), function, true)
+}
+
+/// Convert the color channels of a matrix from linear light to encoded values.
+///
+/// This builds a table for large matrices on every call, use a [`TransferLut`] to convert more
+/// than one image.
+pub fn from_linear<S: TransferSample>(matrix: &mut Matrix<[S; 4]>, function: TransferFunction) {
+    convert_once(matrix.as_mut_slice(), function, false)
+}
+
+fn convert_once<S: TransferSample>(
+    pixels: &mut [[S; 4]],
+    function: TransferFunction,
+    decode: bool,
+) {
+    // The table only pays off when there are more channels than entries. The results are the
+    // same either way.
+    let lut = if pixels.len() * 3 < S::TABLE_LEN {
+        TransferLut::direct(function, decode)
+    } else {
+        TransferLut::new(function, decode)
+    };
+
+    lut.apply_to_pixels(pixels)
+}
+
+impl<S: TransferSample> TransferLut<S> {
+    /// Precompute the conversion from encoded values to linear light.
+    pub fn to_linear(function: TransferFunction) -> Self {
+        Self::new(function, true)
+    }
+
+    /// Precompute the conversion from linear light to encoded values.
+    pub fn from_linear(function: TransferFunction) -> Self {
+        Self::new(function, false)
+    }
+
+    fn new(function: TransferFunction, decode: bool) -> Self {
+        let direct = Self::direct(function, decode);
+        let table = (0..S::TABLE_LEN)
+            .map(|idx| direct.convert(S::from_index(idx)))
+            .collect();
+        TransferLut { table, ..direct }
+    }
+
+    /// A converter without a table, evaluating the function for each value.
+    fn direct(function: TransferFunction, decode: bool) -> Self {
+        TransferLut {
+            function,
+            decode,
+            table: Vec::new(),
+        }
+    }
+
+    /// The transfer function being applied.
+    pub fn function(&self) -> TransferFunction {
+        self.function
+    }
+
+    /// Convert a single channel value.
+    pub fn convert(&self, value: S) -> S {
+        if let Some(&converted) = self.table.get(value.index()) {
+            return converted;
+        }
+
+        let function = self.function;
+        if self.decode {
+            value.evaluate(|v| function.to_linear(v))
+        } else {
+            value.evaluate(|v| function.from_linear(v))
+        }
+    }
+
+    /// Convert the color channels of a matrix.
+    pub fn apply(&self, matrix: &mut Matrix<[S; 4]>) {
+        self.apply_to_pixels(matrix.as_mut_slice())
+    }
+
+    /// Convert the color channels of RGBA pixels, leaving alpha untouched.
+    pub fn apply_to_pixels(&self, pixels: &mut [[S; 4]]) {
+        for [r, g, b, _] in pixels {
+            *r = self.convert(*r);
+            *g = self.convert(*g);
+            *b = self.convert(*b);
+        }
+    }
+}
+
+impl TransferSample for u8 {
+    const TABLE_LEN: usize = 1 << 8;
+
+    fn evaluate(self, function: impl Fn(f32) -> f32) -> Self {
+        let value = function(f32::from(self) / 255.0);
+        (value.min(1.0) * 255.0 + 0.5) as u8
+    }
+
+    fn from_index(index: usize) -> Self {
+        index as u8
+    }
+
+    fn index(self) -> usize {
+        usize::from(self)
+    }
+}
+
+impl TransferSample for u16 {
+    const TABLE_LEN: usize = 1 << 16;
+
+    fn evaluate(self, function: impl Fn(f32) -> f32) -> Self {
+        let value = function(f32::from(self) / 65535.0);
+        (value.min(1.0) * 65535.0 + 0.5) as u16
+    }
+
+    fn from_index(index: usize) -> Self {
+        index as u16
+    }
+
+    fn index(self) -> usize {
+        usize::from(self)
+    }
+}
+
+impl TransferSample for f32 {
+    const TABLE_LEN: usize = 0;
+
+    fn evaluate(self, function: impl Fn(f32) -> f32) -> Self {
+        function(self)
+    }
+
+    fn from_index(index: usize) -> Self {
+        index as f32
+    }
+
+    /// Floats have no table, any index is out of its bounds.
+    fn index(self) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FUNCTIONS: [TransferFunction; 4] = [
+        TransferFunction::Srgb,
+        TransferFunction::Bt1886,
+        TransferFunction::Pq,
+        TransferFunction::Hlg,
+    ];
+
+    #[test]
+    fn reference_values() {
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-4;
+
+        assert!(close(TransferFunction::Srgb.to_linear(0.5), 0.214_041));
+        assert!(close(TransferFunction::Bt1886.to_linear(0.5), 0.189_464));
+        // 100 cd/m² in the perceptual quantizer.
+        assert!(close(TransferFunction::Pq.from_linear(0.01), 0.508_078));
+        assert!(close(TransferFunction::Hlg.to_linear(0.5), 1.0 / 12.0));
+
+        for &function in &FUNCTIONS {
+            assert_eq!(function.to_linear(0.0), 0.0, "{:?}", function);
+            assert!(close(function.to_linear(1.0), 1.0), "{:?}", function);
+            for step in 0..=100 {
+                let v = step as f32 / 100.0;
+                let roundtrip = function.from_linear(function.to_linear(v));
+                assert!(close(roundtrip, v), "{:?} {}", function, v);
+            }
+        }
+    }
+
+    #[test]
+    fn bulk_conversion() {
+        let layout = crate::Layout::width_and_height_for_pixel(
+            crate::pixel::constants::U16.array4(),
+            256,
+            256,
+        )
+        .unwrap();
+        let mut matrix = Matrix::with_layout(layout);
+        matrix
+            .as_mut_slice()
+            .iter_mut()
+            .enumerate()
+            .for_each(|(idx, p)| *p = [idx as u16, idx as u16, idx as u16, idx as u16]);
+        let original = matrix.clone();
+
+        to_linear(&mut matrix, TransferFunction::Pq);
+        assert!(matrix
+            .as_slice()
+            .iter()
+            .all(|p| p[0] == p[1] && p[1] == p[2]));
+        assert!(matrix
+            .as_slice()
+            .iter()
+            .enumerate()
+            .all(|(i, p)| p[3] == i as u16));
+
+        // The table based path for large matrices agrees with direct evaluation.
+        let mut small = Matrix::with_layout(
+            crate::Layout::width_and_height_for_pixel(crate::pixel::constants::U16.array4(), 1, 1)
+                .unwrap(),
+        );
+        small[(0, 0)] = original[(77, 200)];
+        to_linear(&mut small, TransferFunction::Pq);
+        assert_eq!(small[(0, 0)][..3], matrix[(77, 200)][..3]);
+
+        let pixel = crate::pixel::constants::F32.array4();
+        let mut floats =
+            Matrix::with_layout(crate::Layout::width_and_height_for_pixel(pixel, 1, 1).unwrap());
+        floats[(0, 0)] = [0.5, 1.0, 0.0, 0.5];
+        to_linear(&mut floats, TransferFunction::Hlg);
+        from_linear(&mut floats, TransferFunction::Hlg);
+        let [r, g, b, a] = floats[(0, 0)];
+        assert!((r - 0.5).abs() < 1e-5 && (g - 1.0).abs() < 1e-5 && b == 0.0 && a == 0.5);
+    }
+
+    #[test]
+    fn reusable_tables() {
+        for &function in &FUNCTIONS {
+            let decode = TransferLut::<u16>::to_linear(function);
+            let encode = TransferLut::<u16>::from_linear(function);
+            assert_eq!(decode.function(), function);
+
+            for value in (0..=u16::MAX).step_by(257) {
+                let direct = value.evaluate(|v| function.to_linear(v));
+                assert_eq!(decode.convert(value), direct, "{:?} {}", function, value);
+                let direct = value.evaluate(|v| function.from_linear(v));
+                assert_eq!(encode.convert(value), direct, "{:?} {}", function, value);
+            }
+        }
+
+        let decode = TransferLut::<u8>::to_linear(TransferFunction::Srgb);
+        let mut pixels = [[0x80, 0xff, 0x00, 0x80]; 3];
+        decode.apply_to_pixels(&mut pixels);
+        assert_eq!(pixels, [[0x37, 0xff, 0x00, 0x80]; 3]);
+
+        let floats = TransferLut::<f32>::to_linear(TransferFunction::Bt1886);
+        assert_eq!(floats.convert(0.5), TransferFunction::Bt1886.to_linear(0.5));
+    }
+}